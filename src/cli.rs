@@ -0,0 +1,979 @@
+use crate::config::{self, settings};
+use crate::dates;
+use crate::error::{BucketRef, CookieJarError};
+use crate::db::{self, migrations, SortOrder};
+use crate::menu::{get_bucket_color, highlight_snippet, print_stats, sync_after_write};
+use crate::export::{self, ExportFormat};
+use crate::import::{self, ImportFormat};
+use crate::models::Bucket;
+use crate::setup;
+use crate::store::{CookieStore, LibsqlStore};
+use crate::tui;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use colored::*;
+use crossterm::{
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+};
+use dialoguer::{theme::ColorfulTheme, Confirm, Password};
+use std::io::{stdout, IsTerminal};
+use std::path::{Path, PathBuf};
+
+/// Exit codes besides 0, shown at the end of `cj --help`
+const EXIT_CODES: &str = "Exit codes:
+  1  Something went wrong
+  2  The command line wasn't understood
+  3  A bucket with that name already exists
+  4  The cookie is too long
+  5  There's no bucket with that name
+  6  Turso couldn't be reached";
+
+/// Cookie Jar - keep a record of the wins you can reach for when things get hard
+#[derive(Debug, Parser)]
+#[command(name = "cj", version, about, after_help = EXIT_CODES)]
+pub struct Cli {
+    /// Use the local database only, without syncing to Turso
+    #[arg(long, global = true)]
+    pub local: bool,
+
+    /// Use this jar instead of the current one (also COOKIE_JAR_PROFILE)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub jar: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Non-interactive subcommands. Running `cj` without one opens the interactive menu.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Add a new cookie
+    Add {
+        /// Bucket to put the cookie in (created if it doesn't exist). Defaults to `default_bucket`.
+        #[arg(short, long)]
+        bucket: Option<String>,
+
+        /// The cookie itself (max 300 chars, or `max_cookie_length`)
+        content: String,
+    },
+    /// List cookies, newest first
+    List {
+        /// Only list cookies in this bucket
+        #[arg(short, long)]
+        bucket: Option<String>,
+
+        /// Only list cookies with this #tag
+        #[arg(short, long)]
+        tag: Option<String>,
+
+        /// Only list cookies added on or after this date, e.g. 2026-09-01 or "last monday"
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only list cookies added on or before this date, e.g. 2026-09-30 or yesterday
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Show at most this many cookies
+        #[arg(short, long)]
+        limit: Option<i64>,
+
+        /// Skip this many cookies first
+        #[arg(long)]
+        offset: Option<i64>,
+
+        /// List the oldest cookies first
+        #[arg(long)]
+        oldest_first: bool,
+    },
+    /// Browse cookies in a full-screen view
+    Browse {
+        /// Start in this bucket
+        #[arg(short, long)]
+        bucket: Option<String>,
+    },
+    /// Pull a random cookie out of the jar
+    Dip {
+        /// Only reach into this bucket
+        #[arg(short, long)]
+        bucket: Option<String>,
+
+        /// Pick uniformly instead of favouring cookies you haven't seen lately
+        #[arg(short, long)]
+        uniform: bool,
+    },
+    /// Full-text search across all cookies, best matches first
+    Search {
+        /// Words to search for
+        #[arg(required = true)]
+        query: Vec<String>,
+
+        /// Only search cookies with this #tag
+        #[arg(short, long)]
+        tag: Option<String>,
+    },
+    /// Show streaks, activity over time and per-bucket totals
+    Stats {
+        /// Print the stats as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show every #tag with how many cookies carry it
+    Tags,
+    /// List all buckets with cookie counts
+    Buckets,
+    /// Change a cookie's text or move it to another bucket
+    Edit {
+        /// ID of the cookie, as shown by `cj list`
+        id: i64,
+
+        /// New text for the cookie
+        #[arg(short, long)]
+        content: Option<String>,
+
+        /// Move the cookie to this bucket (created if it doesn't exist)
+        #[arg(short, long)]
+        bucket: Option<String>,
+    },
+    /// Delete a cookie
+    Delete {
+        /// ID of the cookie, as shown by `cj list`
+        id: i64,
+
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Write cookies to stdout as JSON, CSV or Markdown
+    Export {
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "json")]
+        format: ExportFormat,
+
+        /// Only export cookies in this bucket
+        #[arg(short, long)]
+        bucket: Option<String>,
+
+        /// Only export cookies added on or after this date, e.g. 2026-09-01 or "last month"
+        #[arg(long)]
+        since: Option<String>,
+    },
+    /// Import cookies from a JSON, CSV or plain-text file
+    Import {
+        /// File to import
+        file: PathBuf,
+
+        /// File format (guessed from the extension if not given)
+        #[arg(short, long, value_enum)]
+        format: Option<ImportFormat>,
+
+        /// Bucket for cookies that don't name one
+        #[arg(short, long, default_value = "Imported")]
+        bucket: String,
+
+        /// Only show what would be imported
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Database maintenance
+    Db {
+        #[command(subcommand)]
+        command: DbCommand,
+    },
+    /// Pull the latest changes from Turso now
+    ///
+    /// While Turso is unreachable, cj opens the copy from the last sync read-only:
+    /// cookies can be browsed but not added or changed until it's back.
+    Sync,
+    /// Copy a local-only jar into a Turso database and sync with it from now on
+    Attach {
+        /// Turso database URL (libsql://...)
+        #[arg(long)]
+        url: String,
+
+        /// Turso auth token (defaults to TURSO_AUTH_TOKEN, or is asked for)
+        #[arg(long)]
+        token: Option<String>,
+    },
+    /// Set up the jar: local-only or synced with Turso, and a first bucket
+    Init,
+    /// Show or change settings in ~/.cookie_jar/config.toml
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Keep separate jars, each with its own cookies and Turso database
+    Jar {
+        #[command(subcommand)]
+        command: JarCommand,
+    },
+}
+
+/// `cj db ...` subcommands
+#[derive(Debug, Subcommand)]
+pub enum DbCommand {
+    /// Apply pending schema migrations
+    Migrate {
+        /// Only show which migrations are applied and pending
+        #[arg(long)]
+        status: bool,
+    },
+}
+
+/// `cj config ...` subcommands
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Print the value in effect for a setting
+    Get {
+        key: String,
+    },
+    /// Store a setting in config.toml. An empty value resets it to the default.
+    Set {
+        key: String,
+        value: String,
+    },
+    /// List every setting with its value and where it comes from
+    List,
+    /// Open config.toml in your editor
+    Edit,
+}
+
+/// `cj jar ...` subcommands
+#[derive(Debug, Subcommand)]
+pub enum JarCommand {
+    /// List your jars
+    List,
+    /// Create a new jar, optionally synced with its own Turso database
+    Create {
+        name: String,
+
+        /// Turso database URL (libsql://...)
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Turso auth token (defaults to TURSO_AUTH_TOKEN, or is asked for)
+        #[arg(long, requires = "url")]
+        token: Option<String>,
+    },
+    /// Use a jar from now on when --jar isn't given
+    Switch {
+        name: String,
+    },
+    /// Delete a jar and its local cookies
+    Remove {
+        name: String,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+/// Run a single subcommand against the database
+pub async fn run(command: Command, store: &LibsqlStore) -> Result<()> {
+    match command {
+        Command::Add { bucket, content } => {
+            let bucket = bucket
+                .or_else(|| settings::current().default_bucket.clone())
+                .context("No bucket given. Pass --bucket, or set one with `cj config set default_bucket <name>`")?;
+            add_cookie(store, &bucket, &content).await
+        }
+        Command::List { bucket, tag, since, until, limit, offset, oldest_first } => {
+            let query = db::CookieQuery::new()
+                .tag(tag.as_deref())
+                .since(since.as_deref().map(dates::parse_since).transpose()?)
+                .until(until.as_deref().map(dates::parse_until).transpose()?)
+                .limit(limit)
+                .offset(offset)
+                .order(if oldest_first { SortOrder::OldestFirst } else { SortOrder::NewestFirst });
+            list_cookies(store, bucket.as_deref(), query).await
+        }
+        Command::Browse { bucket } => browse(store, bucket.as_deref()).await,
+        Command::Dip { bucket, uniform } => dip(store, bucket.as_deref(), uniform).await,
+        Command::Search { query, tag } => search_cookies(store, &query.join(" "), tag.as_deref()).await,
+        Command::Tags => list_tags(store).await,
+        Command::Stats { json } => {
+            let stats = store.stats().await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+            } else {
+                print_stats(&stats);
+            }
+            Ok(())
+        }
+        Command::Buckets => list_buckets(store).await,
+        Command::Edit { id, content, bucket } => {
+            edit_cookie(store, id, content.as_deref(), bucket.as_deref()).await
+        }
+        Command::Delete { id, yes } => delete_cookie(store, id, yes).await,
+        Command::Export { format, bucket, since } => {
+            export(store, format, bucket.as_deref(), since.as_deref()).await
+        }
+        Command::Import { file, format, bucket, dry_run, yes } => {
+            import(store, &file, format, &bucket, dry_run, yes).await
+        }
+        Command::Db { command } => match command {
+            DbCommand::Migrate { status } => migrate(store, status).await,
+        },
+        Command::Sync => sync(store.database()).await,
+        Command::Attach { url, token } => attach(url, token).await,
+        Command::Config { command } => config(command),
+        Command::Jar { command } => jar(command).await,
+        Command::Init => setup::finish(store).await,
+    }
+}
+
+/// `cj add --bucket <name> <content>`
+async fn add_cookie(store: &impl CookieStore, bucket_name: &str, content: &str) -> Result<()> {
+    if content.is_empty() {
+        anyhow::bail!("Cookie cannot be empty");
+    }
+
+    let bucket = get_or_create_bucket(store, bucket_name).await?;
+
+    store.create_cookie(bucket.id, content).await?;
+    sync_after_write(store).await;
+
+    println!(
+        "{} Cookie added to \"{}\" bucket!",
+        "✨".bright_green(),
+        bucket.name.color(get_bucket_color(bucket.id)).bold()
+    );
+
+    Ok(())
+}
+
+/// Look up a bucket by name, creating it if it doesn't exist yet
+async fn get_or_create_bucket(store: &impl CookieStore, name: &str) -> Result<Bucket> {
+    if let Some(bucket) = store.get_bucket_by_name(name).await? {
+        return Ok(bucket);
+    }
+
+    let bucket = store.create_bucket(name).await?;
+    println!(
+        "{} Created bucket \"{}\"",
+        "✓".bright_green(),
+        bucket.name.color(get_bucket_color(bucket.id)).bold()
+    );
+    // Sync immediately after bucket creation to ensure foreign key constraints work
+    sync_after_write(store).await;
+
+    Ok(bucket)
+}
+
+/// `cj list [--bucket <name>] [--tag <tag>] [--since <date>] [--until <date>] [--limit <n>] [--offset <n>]`
+async fn list_cookies(store: &impl CookieStore, bucket_name: Option<&str>, query: db::CookieQuery) -> Result<()> {
+    let bucket_id = match bucket_name {
+        Some(name) => match store.get_bucket_by_name(name).await? {
+            Some(bucket) => Some(bucket.id),
+            None => return Err(CookieJarError::BucketNotFound(BucketRef::Name(name.to_string())).into()),
+        },
+        None => None,
+    };
+
+    for entry in store.query_cookies_with_buckets(&query.bucket(bucket_id)).await? {
+        let cookie = &entry.cookie;
+        println!(
+            "{}  {}  {}  {}",
+            format!("#{}", cookie.id).bright_black(),
+            cookie.formatted_created_at().bright_black(),
+            entry.bucket_name.color(get_bucket_color(cookie.bucket_id)).bold(),
+            cookie.content
+        );
+    }
+
+    Ok(())
+}
+
+/// `cj browse [--bucket <name>]`
+async fn browse(store: &impl CookieStore, bucket_name: Option<&str>) -> Result<()> {
+    let bucket_id = match bucket_name {
+        Some(name) => match store.get_bucket_by_name(name).await? {
+            Some(bucket) => Some(bucket.id),
+            None => return Err(CookieJarError::BucketNotFound(BucketRef::Name(name.to_string())).into()),
+        },
+        None => None,
+    };
+
+    execute!(stdout(), EnterAlternateScreen)?;
+    let result = tui::browse(store, bucket_id).await;
+    execute!(stdout(), LeaveAlternateScreen)?;
+
+    result
+}
+
+/// `cj dip [--bucket <name>] [--uniform]`
+async fn dip(store: &impl CookieStore, bucket_name: Option<&str>, uniform: bool) -> Result<()> {
+    let bucket_id = match bucket_name {
+        Some(name) => match store.get_bucket_by_name(name).await? {
+            Some(bucket) => Some(bucket.id),
+            None => return Err(CookieJarError::BucketNotFound(BucketRef::Name(name.to_string())).into()),
+        },
+        None => None,
+    };
+
+    let mode = if uniform {
+        db::DipMode::Uniform
+    } else {
+        db::DipMode::FavourUnseen
+    };
+
+    let Some(cookie) = store.reach_into_jar(bucket_id, mode).await? else {
+        match bucket_name {
+            Some(name) => anyhow::bail!("No cookies in \"{}\" yet", name),
+            None => anyhow::bail!("The jar is empty"),
+        }
+    };
+    store.record_cookie_view(cookie.id).await?;
+
+    println!("{}", cookie.content.bright_white().bold());
+    println!("{}", cookie.formatted_created_at().bright_black());
+
+    Ok(())
+}
+
+/// `cj search <query>... [--tag <tag>]`
+async fn search_cookies(store: &impl CookieStore, query: &str, tag: Option<&str>) -> Result<()> {
+    let results = store.search_cookies(query, tag).await?;
+
+    for result in &results {
+        let cookie = &result.cookie;
+        println!(
+            "{}  {}  {}  {}",
+            format!("#{}", cookie.id).bright_black(),
+            cookie.formatted_created_at().bright_black(),
+            result.bucket_name.color(get_bucket_color(cookie.bucket_id)).bold(),
+            highlight_snippet(result)
+        );
+    }
+
+    Ok(())
+}
+
+/// `cj tags`
+async fn list_tags(store: &impl CookieStore) -> Result<()> {
+    for (tag, count) in store.get_tag_counts().await? {
+        println!("{}  {} cookies", format!("#{}", tag.name).bright_magenta().bold(), count);
+    }
+
+    Ok(())
+}
+
+/// `cj edit <id> [--content <text>] [--bucket <name>]`
+async fn edit_cookie(
+    store: &impl CookieStore,
+    id: i64,
+    content: Option<&str>,
+    bucket_name: Option<&str>,
+) -> Result<()> {
+    if content.is_none() && bucket_name.is_none() {
+        anyhow::bail!("Nothing to change. Pass --content and/or --bucket");
+    }
+
+    if store.get_cookie(id).await?.is_none() {
+        anyhow::bail!("Cookie #{} does not exist", id);
+    }
+
+    let bucket_id = match bucket_name {
+        Some(name) => Some(get_or_create_bucket(store, name).await?.id),
+        None => None,
+    };
+    store.edit_cookie(id, content, bucket_id).await?;
+
+    sync_after_write(store).await;
+    println!("{} Cookie #{} updated!", "✓".bright_green(), id);
+
+    Ok(())
+}
+
+/// `cj delete <id> [--yes]`
+async fn delete_cookie(store: &impl CookieStore, id: i64, yes: bool) -> Result<()> {
+    let Some(cookie) = store.get_cookie(id).await? else {
+        anyhow::bail!("Cookie #{} does not exist", id);
+    };
+
+    if !yes {
+        let confirmed = Confirm::new()
+            .with_prompt(format!("Delete \"{}\"?", cookie.content))
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            return Ok(());
+        }
+    }
+
+    store.delete_cookie(id).await?;
+    sync_after_write(store).await;
+    println!("{} Cookie #{} deleted", "✓".bright_green(), id);
+
+    Ok(())
+}
+
+/// `cj export [--format json|csv|markdown] [--bucket <name>] [--since <date>]`
+async fn export(
+    store: &impl CookieStore,
+    format: ExportFormat,
+    bucket_name: Option<&str>,
+    since: Option<&str>,
+) -> Result<()> {
+    let query = db::CookieQuery::new().since(since.map(dates::parse_since).transpose()?);
+    let mut buckets = store.get_all_buckets().await?;
+
+    let query = match bucket_name {
+        Some(name) => {
+            let Some(bucket) = buckets.iter().find(|b| b.name == name).cloned() else {
+                return Err(CookieJarError::BucketNotFound(BucketRef::Name(name.to_string())).into());
+            };
+            buckets = vec![bucket];
+            query.bucket(Some(buckets[0].id))
+        }
+        None => query,
+    };
+    let cookies = store.query_cookies(&query).await?;
+
+    export::write_export(&mut std::io::stdout().lock(), format, &cookies, &buckets)
+}
+
+/// `cj import <file> [--format json|csv|text] [--bucket <name>] [--dry-run] [--yes]`
+async fn import(
+    store: &impl CookieStore,
+    file: &Path,
+    format: Option<ImportFormat>,
+    default_bucket: &str,
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
+    let format = format.unwrap_or_else(|| ImportFormat::from_path(file));
+    let input = std::fs::read_to_string(file).context(format!("Failed to read {}", file.display()))?;
+
+    let buckets = store.get_all_buckets().await?;
+    let entries = import::parse(&input, format, default_bucket, &buckets)?;
+    let plan = import::plan(store, entries).await?;
+
+    let position = |entry: &import::ImportEntry| match format {
+        ImportFormat::Text => format!("line {}", entry.position),
+        ImportFormat::Csv => format!("row {}", entry.position),
+        ImportFormat::Json => format!("entry {}", entry.position),
+    };
+
+    println!(
+        "{} {} cookies to import",
+        "→".bright_cyan(),
+        plan.to_import.len().to_string().bright_cyan().bold()
+    );
+    if !plan.new_buckets.is_empty() {
+        println!(
+            "{} {} new buckets: {}",
+            "→".bright_cyan(),
+            plan.new_buckets.len(),
+            plan.new_buckets.join(", ")
+        );
+    }
+    if !plan.duplicates.is_empty() {
+        println!(
+            "{} {} duplicates skipped",
+            "·".bright_black(),
+            plan.duplicates.len()
+        );
+    }
+    for (entry, reason) in &plan.invalid {
+        println!(
+            "{} Skipping {}: {}",
+            "✗".bright_red(),
+            position(entry),
+            reason
+        );
+    }
+
+    if dry_run || plan.to_import.is_empty() {
+        return Ok(());
+    }
+
+    if !yes {
+        let confirmed = Confirm::new()
+            .with_prompt(format!("Import {} cookies?", plan.to_import.len()))
+            .default(true)
+            .interact()?;
+        if !confirmed {
+            return Ok(());
+        }
+    }
+
+    let imported = store.import(&plan).await?;
+    sync_after_write(store).await;
+
+    println!("{} Imported {} cookies", "✨".bright_green(), imported);
+
+    Ok(())
+}
+
+/// `cj db migrate [--status]`
+async fn migrate(store: &impl CookieStore, status_only: bool) -> Result<()> {
+    if status_only {
+        let current = store.schema_version().await?;
+        let applied = store.applied_migrations().await?;
+
+        println!(
+            "Schema version {} (this cj supports up to {})",
+            current.to_string().bright_cyan().bold(),
+            migrations::latest_version()
+        );
+
+        for migration in &applied {
+            let applied_at = chrono::DateTime::from_timestamp(migration.applied_at, 0)
+                .unwrap_or_default()
+                .with_timezone(&chrono::Local);
+            println!(
+                "  {} {:>3}  {}  {}",
+                "✓".bright_green(),
+                migration.version,
+                migration.description,
+                format!("applied {}", applied_at.format("%b %d, %Y")).bright_black()
+            );
+        }
+
+        for migration in store.pending_migrations().await? {
+            println!(
+                "  {} {:>3}  {}  {}",
+                "·".bright_yellow(),
+                migration.version,
+                migration.description,
+                "pending".bright_yellow()
+            );
+        }
+
+        return Ok(());
+    }
+
+    let applied = store.migrate().await?;
+    sync_after_write(store).await;
+
+    if applied.is_empty() {
+        println!("{} Schema is already up to date", "✓".bright_green());
+    }
+    for migration in &applied {
+        println!(
+            "{} Applied migration {}: {}",
+            "✓".bright_green(),
+            migration.version,
+            migration.description
+        );
+    }
+
+    Ok(())
+}
+
+/// `cj buckets`
+async fn list_buckets(store: &impl CookieStore) -> Result<()> {
+    for stats in store.get_buckets_with_stats().await? {
+        let bucket = &stats.bucket;
+        println!(
+            "{}  {} cookies  {}",
+            bucket.name.color(get_bucket_color(bucket.id)).bold(),
+            stats.cookie_count,
+            stats.last_added().bright_black()
+        );
+    }
+
+    Ok(())
+}
+
+/// `cj sync`
+async fn sync(db: &db::Database) -> Result<()> {
+    let started = std::time::Instant::now();
+
+    let Some(report) = db.sync().await? else {
+        println!(
+            "{} This jar is local-only, so there is nothing to sync. Use `cj attach` to connect it to Turso.",
+            "ℹ".bright_yellow()
+        );
+        return Ok(());
+    };
+
+    let frame = match report.frame_no {
+        Some(frame_no) => format!("now at frame {}", frame_no),
+        None => "no frames replicated yet".to_string(),
+    };
+    println!(
+        "{} Synced with Turso in {:.1?}: pulled {} frames, {}",
+        "✓".bright_green(),
+        started.elapsed(),
+        report.frames_synced.to_string().bright_cyan().bold(),
+        frame
+    );
+    println!(
+        "   {}",
+        "Changes made here are sent to Turso as they happen, so there is nothing to push.".bright_black()
+    );
+
+    Ok(())
+}
+
+/// `cj attach --url <url> [--token <token>]`
+///
+/// Must run before the jar is opened, since the local file is moved aside so the
+/// remote replica can be created in its place.
+pub async fn attach(url: String, token: Option<String>) -> Result<()> {
+    if db::remote_credentials().is_some() {
+        anyhow::bail!("This jar is already attached to Turso (TURSO_DATABASE_URL is set)");
+    }
+
+    let token = auth_token(token)?;
+    attach_jar(&url, &token).await?;
+
+    println!("{} Jar attached to Turso. It will sync from now on.", "✨".bright_green());
+
+    Ok(())
+}
+
+/// The Turso auth token from `--token`, else TURSO_AUTH_TOKEN, else typed in without being
+/// shown, so it needn't be left in shell history
+fn auth_token(token: Option<String>) -> Result<String> {
+    if let Some(token) = token.or_else(|| std::env::var("TURSO_AUTH_TOKEN").ok()).filter(|t| !t.trim().is_empty()) {
+        return Ok(token.trim().to_string());
+    }
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("No Turso auth token: pass --token or set TURSO_AUTH_TOKEN");
+    }
+    let token = Password::with_theme(&ColorfulTheme::default())
+        .with_prompt("Turso auth token")
+        .interact()?;
+    Ok(token.trim().to_string())
+}
+
+/// Copy the local jar (if there is one) into Turso, back it up, and save the credentials
+pub async fn attach_jar(url: &str, token: &str) -> Result<()> {
+    let db_path = config::get_db_path()?;
+
+    if db_path.exists() {
+        let summary = db::attach_remote(db_path.clone(), url.to_string(), token.to_string()).await?;
+
+        let backup_path = config::get_local_backup_path()?;
+        for suffix in ["", "-wal", "-shm"] {
+            let from = format!("{}{}", db_path.display(), suffix);
+            if std::path::Path::new(&from).exists() {
+                std::fs::rename(&from, format!("{}{}", backup_path.display(), suffix))?;
+            }
+        }
+
+        println!(
+            "{} Copied {} cookies ({} new buckets, {} already present)",
+            "✓".bright_green(),
+            summary.cookies_copied.to_string().bright_cyan().bold(),
+            summary.buckets_created,
+            summary.cookies_skipped
+        );
+        println!(
+            "   {} Local jar backed up to {}",
+            "🗄".bright_black(),
+            backup_path.display().to_string().bright_black()
+        );
+    }
+
+    config::update_env_file(&[
+        ("TURSO_DATABASE_URL", Some(url)),
+        ("TURSO_AUTH_TOKEN", Some(token)),
+        ("COOKIE_JAR_STORAGE", None),
+    ])?;
+    if settings::Documents::load()?.contains(settings::key("storage")?) {
+        settings::set("storage", "")?;
+    }
+
+    Ok(())
+}
+
+/// `cj config get|set|list|edit`
+pub fn config(command: ConfigCommand) -> Result<()> {
+    match command {
+        ConfigCommand::Get { key } => {
+            let key = settings::key(&key)?;
+            let (value, _) = settings::lookup(&settings::Documents::load()?, key)?;
+            println!("{}", value);
+        }
+        ConfigCommand::Set { key, value } => {
+            let key = settings::key(&key)?;
+            settings::set(key.name, &value)?;
+            if value.trim().is_empty() {
+                println!("{} {} reset to its default", "✓".bright_green(), key.name.bold());
+            } else {
+                println!("{} {} = {}", "✓".bright_green(), key.name.bold(), value.trim().bright_cyan());
+            }
+            if std::env::var(key.env).is_ok_and(|value| !value.trim().is_empty()) {
+                println!(
+                    "   {}",
+                    format!("{} is set in the environment and takes precedence over config.toml", key.env).bright_yellow()
+                );
+            }
+        }
+        ConfigCommand::List => {
+            let docs = settings::Documents::load()?;
+            println!("{}", "⚙️  Settings".bright_cyan().bold());
+            println!("{}", "─".repeat(60).bright_black());
+            for key in settings::KEYS {
+                let (value, source) = settings::lookup(&docs, key)?;
+                let value = if value.is_empty() { "(not set)".bright_black() } else { value.normal() };
+                println!(
+                    "{:<18} {}  {}",
+                    key.name.bold(),
+                    value,
+                    format!("({})", source.describe(key)).bright_black()
+                );
+            }
+            println!("{}", "─".repeat(60).bright_black());
+            println!("{}", config::get_config_path()?.display().to_string().bright_black());
+            if config::active_jar() != config::DEFAULT_JAR {
+                println!(
+                    "{}",
+                    format!("Shared with every jar: {}", config::get_shared_config_path()?.display()).bright_black()
+                );
+            }
+        }
+        ConfigCommand::Edit => edit_config()?,
+    }
+    Ok(())
+}
+
+/// Open config.toml in the configured editor, then check what was saved
+fn edit_config() -> Result<()> {
+    let path = config::get_config_path()?;
+    if !path.exists() {
+        config::write_private(&path, &settings::template(config::active_jar() == config::DEFAULT_JAR))?;
+    }
+
+    let editor = settings::Settings::load()
+        .ok()
+        .and_then(|settings| settings.editor)
+        .or_else(|| std::env::var("VISUAL").ok().filter(|editor| !editor.trim().is_empty()))
+        .or_else(|| std::env::var("EDITOR").ok().filter(|editor| !editor.trim().is_empty()))
+        .unwrap_or_else(|| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().context("The editor setting is empty")?;
+
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("Failed to run editor '{}'", editor))?;
+    if !status.success() {
+        anyhow::bail!("Editor '{}' exited with {}", editor, status);
+    }
+
+    match settings::Settings::load() {
+        Ok(_) => println!("{} Saved {}", "✓".bright_green(), path.display()),
+        Err(e) => println!(
+            "{} Saved, but the settings have a problem: {:#}\n   Run `cj config edit` again to fix it.",
+            "⚠".bright_yellow(),
+            e
+        ),
+    }
+    Ok(())
+}
+
+/// `cj jar list|create|switch|remove`
+pub async fn jar(command: JarCommand) -> Result<()> {
+    match command {
+        JarCommand::List => {
+            let current = config::load_current_jar().unwrap_or_else(|| config::DEFAULT_JAR.to_string());
+            println!("{}", "🫙 Jars".bright_cyan().bold());
+            println!("{}", "─".repeat(60).bright_black());
+            for name in config::list_jars()? {
+                let marker = if name == config::active_jar() { "→".bright_green() } else { " ".normal() };
+                let label = if name == current { format!("{} (current)", name) } else { name.clone() };
+                let dir = config::get_jar_dir_for(&name)?;
+                let storage = if jar_syncs(&dir) { "synced with Turso" } else { "local-only" };
+                println!("{} {:<24} {}", marker, label.bold(), storage.bright_black());
+            }
+            println!("{}", "─".repeat(60).bright_black());
+        }
+        JarCommand::Create { name, url, token } => {
+            config::validate_jar_name(&name)?;
+            let dir = config::get_jar_dir_for(&name)?;
+            if name == config::DEFAULT_JAR || dir.exists() {
+                anyhow::bail!("There's already a jar called '{}'", name);
+            }
+
+            let token = match url {
+                Some(_) => Some(auth_token(token)?),
+                None => None,
+            };
+            if let (Some(url), Some(token)) = (&url, &token) {
+                settings::check("turso_url", url)?;
+                db::check_remote(url, token)
+                    .await
+                    .context("Couldn't connect to Turso with that URL and token")?;
+            }
+
+            std::fs::create_dir_all(&dir).context(format!("Failed to create directory: {}", dir.display()))?;
+            if let (Some(url), Some(token)) = (&url, &token) {
+                let env = format!("TURSO_DATABASE_URL={}\nTURSO_AUTH_TOKEN={}\n", url, token);
+                config::write_private(&dir.join(".env"), &env)?;
+            }
+
+            println!("{} Created jar \"{}\"", "✓".bright_green(), name.bold());
+            println!(
+                "   {}",
+                format!("Use it with `cj --jar {} ...`, or make it the default with `cj jar switch {}`", name, name)
+                    .bright_black()
+            );
+        }
+        JarCommand::Switch { name } => {
+            config::validate_jar_name(&name)?;
+            if !config::list_jars()?.contains(&name) {
+                anyhow::bail!("There's no jar called '{}'. Create it with `cj jar create {}`", name, name);
+            }
+            config::save_current_jar(&name)?;
+            println!("{} Now using the \"{}\" jar", "✓".bright_green(), name.bold());
+            if std::env::var("COOKIE_JAR_PROFILE").is_ok_and(|profile| !profile.trim().is_empty()) {
+                println!(
+                    "   {}",
+                    "COOKIE_JAR_PROFILE is set in the environment and takes precedence".bright_yellow()
+                );
+            }
+        }
+        JarCommand::Remove { name, yes } => {
+            if name == config::DEFAULT_JAR {
+                anyhow::bail!("The default jar can't be removed");
+            }
+            config::validate_jar_name(&name)?;
+            let dir = config::get_jar_dir_for(&name)?;
+            if !dir.exists() {
+                anyhow::bail!("There's no jar called '{}'", name);
+            }
+
+            if !yes {
+                let note = if jar_syncs(&dir) { " Its Turso database is left as it is." } else { "" };
+                println!("Removing deletes {} and every cookie in it.{}", dir.display(), note);
+                let confirmed = Confirm::new()
+                    .with_prompt(format!("Remove the \"{}\" jar?", name))
+                    .default(false)
+                    .interact()?;
+                if !confirmed {
+                    println!("{}", "Cancelled".bright_black());
+                    return Ok(());
+                }
+            }
+
+            std::fs::remove_dir_all(&dir).context(format!("Failed to remove {}", dir.display()))?;
+            if config::load_current_jar().as_deref() == Some(name.as_str()) {
+                config::save_current_jar(config::DEFAULT_JAR)?;
+            }
+            println!("{} Removed jar \"{}\"", "🗑".bright_red(), name.bold());
+        }
+    }
+    Ok(())
+}
+
+/// Whether a jar has Turso credentials in its .env or a URL in its config.toml
+fn jar_syncs(dir: &Path) -> bool {
+    let in_env = dotenvy::from_path_iter(dir.join(".env"))
+        .map(|vars| vars.flatten().any(|(key, value)| key == "TURSO_DATABASE_URL" && !value.is_empty()))
+        .unwrap_or(false);
+    let in_config = std::fs::read_to_string(dir.join("config.toml"))
+        .is_ok_and(|contents| contents.lines().any(|line| line.trim_start().starts_with("turso_url")));
+    in_env || in_config
+}
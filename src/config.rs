@@ -1,5 +1,9 @@
+pub mod settings;
+
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 /// Get the path to the cookie_jar directory ($HOME/.cookie_jar)
 pub fn get_cookiejar_dir() -> Result<PathBuf> {
@@ -13,23 +17,89 @@ pub fn get_cookiejar_dir() -> Result<PathBuf> {
     Ok(path)
 }
 
+/// Get the directory holding the active jar's files: the cookie_jar directory itself
+/// for the default jar, or jars/<name> inside it for a named one
+pub fn get_jar_dir() -> Result<PathBuf> {
+    get_jar_dir_for(active_jar())
+}
+
+/// Get the directory holding a jar's files
+pub fn get_jar_dir_for(name: &str) -> Result<PathBuf> {
+    let mut dir = get_cookiejar_dir()?;
+    if name != DEFAULT_JAR {
+        dir.push("jars");
+        dir.push(name);
+    }
+    Ok(dir)
+}
+
 /// Get the path to the local database file
 pub fn get_db_path() -> Result<PathBuf> {
-    let mut dir = get_cookiejar_dir()?;
+    let mut dir = get_jar_dir()?;
     dir.push("cookie_jar.db");
     Ok(dir)
 }
 
+/// Get a fresh path to move a local-only database to when attaching it to Turso
+pub fn get_local_backup_path() -> Result<PathBuf> {
+    let mut dir = get_jar_dir()?;
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    dir.push(format!("cookie_jar.local-backup-{}.db", stamp));
+    Ok(dir)
+}
+
 /// Get the path to the .env file
 pub fn get_env_path() -> Result<PathBuf> {
-    let mut dir = get_cookiejar_dir()?;
+    let mut dir = get_jar_dir()?;
     dir.push(".env");
     Ok(dir)
 }
 
-/// Ensure the cookie_jar directory exists, create it if it doesn't
+/// Get the path to the active jar's config.toml settings file
+pub fn get_config_path() -> Result<PathBuf> {
+    let mut dir = get_jar_dir()?;
+    dir.push("config.toml");
+    Ok(dir)
+}
+
+/// Get the path to the default jar's config.toml, which also holds the settings every jar shares
+pub fn get_shared_config_path() -> Result<PathBuf> {
+    let mut dir = get_cookiejar_dir()?;
+    dir.push("config.toml");
+    Ok(dir)
+}
+
+/// Get the path to the file remembering which jar `cj jar switch` picked
+pub fn get_current_jar_path() -> Result<PathBuf> {
+    let mut dir = get_cookiejar_dir()?;
+    dir.push("current_jar");
+    Ok(dir)
+}
+
+/// Get the path to the file recording when the jar last synced with Turso
+pub fn get_last_sync_path() -> Result<PathBuf> {
+    let mut dir = get_jar_dir()?;
+    dir.push("last_sync");
+    Ok(dir)
+}
+
+/// When the jar last synced with Turso, if it ever has
+pub fn load_last_sync() -> Option<DateTime<Utc>> {
+    let contents = std::fs::read_to_string(get_last_sync_path().ok()?).ok()?;
+    DateTime::parse_from_rfc3339(contents.trim())
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// Remember when the jar last synced with Turso
+pub fn save_last_sync(time: DateTime<Utc>) -> Result<()> {
+    let path = get_last_sync_path()?;
+    std::fs::write(&path, time.to_rfc3339()).context(format!("Failed to write {}", path.display()))
+}
+
+/// Ensure the active jar's directory (and the cookie_jar directory) exists, create it if it doesn't
 pub fn ensure_cookiejar_dir() -> Result<PathBuf> {
-    let dir = get_cookiejar_dir()?;
+    let dir = get_jar_dir()?;
 
     if !dir.exists() {
         std::fs::create_dir_all(&dir)
@@ -38,3 +108,136 @@ pub fn ensure_cookiejar_dir() -> Result<PathBuf> {
 
     Ok(dir)
 }
+
+// ============ JARS ============
+
+/// The jar used when none is picked
+pub const DEFAULT_JAR: &str = "default";
+
+static ACTIVE_JAR: OnceLock<String> = OnceLock::new();
+
+/// Pick the jar for this run: `--jar`, then COOKIE_JAR_PROFILE, then the one chosen
+/// with `cj jar switch`, then the default jar
+pub fn select_jar(flag: Option<&str>) -> Result<()> {
+    let name = match flag {
+        Some(name) => name.trim().to_string(),
+        None => match std::env::var("COOKIE_JAR_PROFILE") {
+            Ok(name) if !name.trim().is_empty() => name.trim().to_string(),
+            _ => load_current_jar().unwrap_or_else(|| DEFAULT_JAR.to_string()),
+        },
+    };
+    validate_jar_name(&name)?;
+    let _ = ACTIVE_JAR.set(name);
+    Ok(())
+}
+
+/// The jar this run uses
+pub fn active_jar() -> &'static str {
+    ACTIVE_JAR.get().map(String::as_str).unwrap_or(DEFAULT_JAR)
+}
+
+/// Fail with a hint if the active jar hasn't been created
+pub fn ensure_jar_exists() -> Result<()> {
+    let name = active_jar();
+    if name != DEFAULT_JAR && !get_jar_dir_for(name)?.exists() {
+        anyhow::bail!(
+            "There's no jar called '{}'. Create it with `cj jar create {}`, or see your jars with `cj jar list`",
+            name,
+            name
+        );
+    }
+    Ok(())
+}
+
+/// The jar `cj jar switch` last picked, if any
+pub fn load_current_jar() -> Option<String> {
+    let name = std::fs::read_to_string(get_current_jar_path().ok()?).ok()?;
+    Some(name.trim().to_string()).filter(|name| !name.is_empty())
+}
+
+/// Make `name` the jar used when neither `--jar` nor COOKIE_JAR_PROFILE is given
+pub fn save_current_jar(name: &str) -> Result<()> {
+    let path = get_current_jar_path()?;
+    if name == DEFAULT_JAR {
+        if path.exists() {
+            std::fs::remove_file(&path).context(format!("Failed to remove {}", path.display()))?;
+        }
+        return Ok(());
+    }
+    std::fs::write(&path, name).context(format!("Failed to write {}", path.display()))
+}
+
+/// Every jar that exists: the default one, then named jars alphabetically
+pub fn list_jars() -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(get_cookiejar_dir()?.join("jars")) {
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                names.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+    }
+    names.sort();
+    names.insert(0, DEFAULT_JAR.to_string());
+    Ok(names)
+}
+
+/// Jar names become directory names, so keep them to letters, digits, '-' and '_'
+pub fn validate_jar_name(name: &str) -> Result<()> {
+    let valid = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if name.is_empty() || !valid {
+        anyhow::bail!("Jar names can only use letters, digits, '-' and '_', so '{}' won't work", name);
+    }
+    Ok(())
+}
+
+/// Set (or replace) variables in the .env file, keeping any other lines intact.
+/// Variables mapped to `None` are removed.
+pub fn update_env_file(vars: &[(&str, Option<&str>)]) -> Result<()> {
+    let env_path = get_env_path()?;
+    let existing = std::fs::read_to_string(&env_path).unwrap_or_default();
+
+    let mut lines: Vec<String> = existing
+        .lines()
+        .filter(|line| {
+            let key = line.split('=').next().unwrap_or("").trim();
+            !vars.iter().any(|(name, _)| *name == key)
+        })
+        .map(str::to_string)
+        .collect();
+
+    for (name, value) in vars {
+        if let Some(value) = value {
+            lines.push(format!("{}={}", name, value));
+        }
+    }
+
+    write_private(&env_path, &(lines.join("\n") + "\n"))
+}
+
+/// Write a file only the current user can read, since it may hold credentials. A new file
+/// is created 0600, and an existing one is narrowed to 0600 before anything is written to it.
+pub fn write_private(path: &Path, contents: &str) -> Result<()> {
+    use std::io::Write;
+
+    ensure_cookiejar_dir()?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path).context(format!("Failed to write {}", path.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))
+            .context(format!("Failed to restrict permissions on {}", path.display()))?;
+    }
+
+    file.write_all(contents.as_bytes()).context(format!("Failed to write {}", path.display()))?;
+
+    Ok(())
+}
@@ -0,0 +1,346 @@
+use super::{get_config_path, get_shared_config_path};
+use anyhow::{Context, Result};
+use chrono::format::{Item, StrftimeItems};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use toml_edit::{value, DocumentMut};
+
+/// The longest cookie `max_cookie_length` can allow, in characters as `db::cookie_length` counts them
+pub const MAX_COOKIE_LENGTH: usize = 300;
+
+/// A setting that can be kept in config.toml
+pub struct Key {
+    pub name: &'static str,
+    /// Environment variable that overrides the file
+    pub env: &'static str,
+    pub default: &'static str,
+    pub help: &'static str,
+    /// Written to the file as a number rather than a string
+    numeric: bool,
+    /// Kept in each jar's own config.toml rather than shared by every jar
+    per_jar: bool,
+}
+
+impl Key {
+    /// The config.toml this setting is kept in
+    pub fn path(&self) -> Result<PathBuf> {
+        if self.per_jar { get_config_path() } else { get_shared_config_path() }
+    }
+}
+
+/// Every setting config.toml understands, in the order `cj config list` shows them
+pub const KEYS: &[Key] = &[
+    Key { name: "storage", env: "COOKIE_JAR_STORAGE", default: "auto", numeric: false, per_jar: true,
+          help: "\"auto\" uses Turso when credentials are set, \"local\" never does" },
+    Key { name: "turso_url", env: "TURSO_DATABASE_URL", default: "", numeric: false, per_jar: true,
+          help: "Turso database URL (the auth token stays in .env)" },
+    Key { name: "sync", env: "COOKIE_JAR_SYNC", default: "interval", numeric: false, per_jar: true,
+          help: "When to sync with Turso: \"interval\", \"on-exit\" or \"manual\"" },
+    Key { name: "sync_interval", env: "COOKIE_JAR_SYNC_INTERVAL", default: "60", numeric: true, per_jar: true,
+          help: "Seconds between background syncs" },
+    Key { name: "default_bucket", env: "COOKIE_JAR_DEFAULT_BUCKET", default: "", numeric: false, per_jar: true,
+          help: "Bucket `cj add` uses when none is given" },
+    Key { name: "date_format", env: "COOKIE_JAR_DATE_FORMAT", default: "%b %d, %Y at %I:%M %p", numeric: false, per_jar: false,
+          help: "strftime format for cookie dates" },
+    Key { name: "theme", env: "COOKIE_JAR_THEME", default: "pastel", numeric: false, per_jar: false,
+          help: "\"pastel\" for coloured buckets, \"plain\" for no colour" },
+    Key { name: "max_cookie_length", env: "COOKIE_JAR_MAX_COOKIE_LENGTH", default: "300", numeric: true, per_jar: false,
+          help: "Longest cookie you can add in characters, counting an emoji as one, up to 300" },
+    Key { name: "editor", env: "COOKIE_JAR_EDITOR", default: "", numeric: false, per_jar: false,
+          help: "Command `cj config edit` opens (defaults to $VISUAL, then $EDITOR)" },
+];
+
+/// Where a setting's value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Env,
+    File,
+    Default,
+}
+
+impl Source {
+    pub fn describe(&self, key: &Key) -> String {
+        match self {
+            Source::Env => format!("from {}", key.env),
+            Source::File if key.per_jar || super::active_jar() == super::DEFAULT_JAR => "from config.toml".to_string(),
+            Source::File => "from the default jar's config.toml".to_string(),
+            Source::Default => "default".to_string(),
+        }
+    }
+}
+
+/// Whether to use Turso at all
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Storage {
+    /// Turso when credentials are set, a local file otherwise
+    Auto,
+    /// Always a local file
+    Local,
+}
+
+/// When a Turso-backed jar syncs with the remote
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// Sync on startup, in the background every so often, and on exit
+    Interval(Duration),
+    /// Sync once, when cj exits
+    OnExit,
+    /// Only sync when asked to with `cj sync`
+    Manual,
+}
+
+/// How much colour to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Pastel,
+    Plain,
+}
+
+/// Typed settings, resolved from the environment, config.toml and defaults
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub storage: Storage,
+    pub turso_url: Option<String>,
+    pub sync: SyncPolicy,
+    pub default_bucket: Option<String>,
+    pub date_format: String,
+    pub theme: Theme,
+    pub max_cookie_length: usize,
+    pub editor: Option<String>,
+}
+
+/// The active jar's config.toml, and the default jar's one that holds settings shared by every jar
+#[derive(Debug, Default)]
+pub struct Documents {
+    jar: DocumentMut,
+    shared: DocumentMut,
+}
+
+impl Documents {
+    pub fn load() -> Result<Self> {
+        Ok(Self {
+            jar: read_document(&get_config_path()?)?,
+            shared: read_document(&get_shared_config_path()?)?,
+        })
+    }
+
+    fn get(&self, key: &Key) -> &DocumentMut {
+        if key.per_jar { &self.jar } else { &self.shared }
+    }
+
+    /// Whether a setting is written in its config.toml
+    pub fn contains(&self, key: &Key) -> bool {
+        self.get(key).contains_key(key.name)
+    }
+}
+
+static CURRENT: OnceLock<Settings> = OnceLock::new();
+
+/// The settings in effect. Unless `install` has been called first, they're loaded from
+/// the environment and config.toml on first use, falling back to the defaults if that fails.
+pub fn current() -> &'static Settings {
+    CURRENT.get_or_init(|| Settings::load().unwrap_or_else(|_| Settings::defaults()))
+}
+
+/// Make these the settings in effect for the rest of the run
+pub fn install(settings: Settings) {
+    let _ = CURRENT.set(settings);
+}
+
+impl Settings {
+    /// Load settings, letting environment variables override config.toml
+    pub fn load() -> Result<Self> {
+        Self::resolve(&Documents::load()?, true)
+    }
+
+    /// Every setting at its default, ignoring the environment and config.toml
+    pub fn defaults() -> Self {
+        Self::resolve(&Documents::default(), false).expect("defaults are valid")
+    }
+
+    fn resolve(docs: &Documents, use_env: bool) -> Result<Self> {
+        let setting = |name: &str| -> Result<(&'static Key, String, Source)> {
+            let key = key(name)?;
+            let (value, source) = if use_env { lookup(docs, key)? } else { lookup_file(docs, key)? };
+            Ok((key, value, source))
+        };
+        let parse = |name: &str| -> Result<String> {
+            let (key, value, source) = setting(name)?;
+            check(key.name, &value).with_context(|| match source {
+                Source::Env => format!("Invalid {} in the environment", key.env),
+                Source::File => format!("Invalid {} in {}", key.name, path_display(key)),
+                Source::Default => format!("Invalid default for {}", key.name),
+            })?;
+            Ok(value)
+        };
+
+        let interval = Duration::from_secs(parse("sync_interval")?.parse()?);
+        let sync = match parse_sync(&parse("sync")?)? {
+            SyncPolicy::Interval(_) => SyncPolicy::Interval(interval),
+            other => other,
+        };
+
+        Ok(Settings {
+            storage: parse_storage(&parse("storage")?)?,
+            turso_url: optional(parse("turso_url")?),
+            sync,
+            default_bucket: optional(parse("default_bucket")?),
+            date_format: parse("date_format")?,
+            theme: parse_theme(&parse("theme")?)?,
+            max_cookie_length: parse("max_cookie_length")?.parse()?,
+            editor: optional(parse("editor")?),
+        })
+    }
+}
+
+/// Look up a setting by name
+pub fn key(name: &str) -> Result<&'static Key> {
+    if let Some(key) = KEYS.iter().find(|key| key.name == name) {
+        return Ok(key);
+    }
+    if name.contains("token") {
+        anyhow::bail!("The Turso auth token is a secret, so it lives in ~/.cookie_jar/.env as TURSO_AUTH_TOKEN rather than in config.toml");
+    }
+    let names: Vec<&str> = KEYS.iter().map(|key| key.name).collect();
+    anyhow::bail!("Unknown setting '{}'. Settings are: {}", name, names.join(", "))
+}
+
+/// A setting's value and where it came from: the environment, then config.toml, then the default
+pub fn lookup(docs: &Documents, key: &Key) -> Result<(String, Source)> {
+    match std::env::var(key.env) {
+        Ok(value) if !value.trim().is_empty() => Ok((value.trim().to_string(), Source::Env)),
+        _ => lookup_file(docs, key),
+    }
+}
+
+fn lookup_file(docs: &Documents, key: &Key) -> Result<(String, Source)> {
+    let Some(item) = docs.get(key).get(key.name) else {
+        return Ok((key.default.to_string(), Source::Default));
+    };
+    let value = if let Some(text) = item.as_str() {
+        text.trim().to_string()
+    } else if let Some(number) = item.as_integer() {
+        number.to_string()
+    } else {
+        anyhow::bail!("{} in {} must be a string or a number", key.name, path_display(key));
+    };
+    Ok((value, Source::File))
+}
+
+/// Check a value is valid for a setting, explaining what's expected if not
+pub fn check(name: &str, value: &str) -> Result<()> {
+    match name {
+        "storage" => parse_storage(value).map(drop),
+        "turso_url" => parse_url(value),
+        "sync" => parse_sync(value).map(drop),
+        "sync_interval" => match value.parse::<u64>() {
+            Ok(secs) if secs > 0 => Ok(()),
+            _ => anyhow::bail!("sync_interval must be a whole number of seconds, not \"{}\"", value),
+        },
+        "date_format" => parse_date_format(value),
+        "theme" => parse_theme(value).map(drop),
+        "max_cookie_length" => match value.parse::<usize>() {
+            Ok(length) if (1..=MAX_COOKIE_LENGTH).contains(&length) => Ok(()),
+            _ => anyhow::bail!("max_cookie_length must be a number from 1 to {}, not \"{}\"", MAX_COOKIE_LENGTH, value),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Store a setting in its config.toml, or remove it when `value` is empty.
+/// Comments and other settings in the file are kept as they are.
+pub fn set(name: &str, new_value: &str) -> Result<()> {
+    let key = key(name)?;
+    let new_value = new_value.trim();
+    check(key.name, new_value)?;
+
+    let path = key.path()?;
+    let mut doc = read_document(&path)?;
+    if new_value.is_empty() {
+        doc.remove(key.name);
+    } else if key.numeric {
+        doc[key.name] = value(new_value.parse::<i64>()?);
+    } else {
+        doc[key.name] = value(new_value);
+    }
+
+    super::write_private(&path, &doc.to_string())
+}
+
+/// Read a config.toml, treating a missing file as empty
+fn read_document(path: &Path) -> Result<DocumentMut> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(DocumentMut::new()),
+        Err(e) => return Err(e).context(format!("Failed to read {}", path.display())),
+    };
+    contents
+        .parse()
+        .with_context(|| format!("{} is not valid TOML. Fix it with `cj config edit`", path.display()))
+}
+
+/// A commented config.toml listing its settings with their defaults. A named jar's
+/// file only has the settings kept per jar; the rest come from the default jar's.
+pub fn template(shared: bool) -> String {
+    let mut out = String::from(
+        "# cookie jar settings. Environment variables override these, and\n\
+         # command-line flags override both. Uncomment a line to change it.\n",
+    );
+    for key in KEYS.iter().filter(|key| shared || key.per_jar) {
+        let default = if key.numeric { key.default.to_string() } else { format!("\"{}\"", key.default) };
+        out.push_str(&format!("\n# {} ({})\n# {} = {}\n", key.help, key.env, key.name, default));
+    }
+    out
+}
+
+fn path_display(key: &Key) -> String {
+    key.path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| "config.toml".to_string())
+}
+
+fn optional(value: String) -> Option<String> {
+    Some(value).filter(|value| !value.is_empty())
+}
+
+fn parse_storage(value: &str) -> Result<Storage> {
+    match value.to_lowercase().as_str() {
+        "auto" => Ok(Storage::Auto),
+        "local" => Ok(Storage::Local),
+        _ => anyhow::bail!("storage must be \"auto\" or \"local\", not \"{}\"", value),
+    }
+}
+
+fn parse_url(value: &str) -> Result<()> {
+    const SCHEMES: [&str; 5] = ["libsql://", "https://", "http://", "wss://", "ws://"];
+    if value.is_empty() || SCHEMES.iter().any(|scheme| value.starts_with(scheme)) {
+        Ok(())
+    } else {
+        anyhow::bail!("turso_url should look like libsql://your-db.turso.io, not \"{}\"", value)
+    }
+}
+
+fn parse_sync(value: &str) -> Result<SyncPolicy> {
+    match value.to_lowercase().as_str() {
+        "interval" => Ok(SyncPolicy::Interval(Duration::ZERO)),
+        "on-exit" => Ok(SyncPolicy::OnExit),
+        "manual" => Ok(SyncPolicy::Manual),
+        _ => anyhow::bail!("sync must be \"interval\", \"on-exit\" or \"manual\", not \"{}\"", value),
+    }
+}
+
+fn parse_date_format(value: &str) -> Result<()> {
+    if value.is_empty() || StrftimeItems::new(value).any(|item| matches!(item, Item::Error)) {
+        anyhow::bail!("date_format \"{}\" isn't a valid strftime format. Try \"%Y-%m-%d %H:%M\"", value);
+    }
+    Ok(())
+}
+
+fn parse_theme(value: &str) -> Result<Theme> {
+    match value.to_lowercase().as_str() {
+        "pastel" => Ok(Theme::Pastel),
+        "plain" => Ok(Theme::Plain),
+        _ => anyhow::bail!("theme must be \"pastel\" or \"plain\", not \"{}\"", value),
+    }
+}
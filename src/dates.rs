@@ -0,0 +1,152 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, Utc, Weekday};
+
+const MONTH_NAMES: [&str; 12] = [
+    "january", "february", "march", "april", "may", "june",
+    "july", "august", "september", "october", "november", "december",
+];
+
+/// A span of time, either end of which may be open
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DateRange {
+    /// Inclusive lower bound
+    pub since: Option<DateTime<Utc>>,
+    /// Exclusive upper bound
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// Parse a date the way people type it, relative to `today`.
+///
+/// Understands `2026-09-30`, `sep 30`, `30 september 2026`, `today`, `yesterday`,
+/// weekdays (`monday` is this week's, `last monday` the one before today),
+/// `this week|month|year`, `last week|month|year` and `N days|weeks|months|years ago`.
+pub fn parse_date(input: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let text = input.trim().to_lowercase();
+
+    if let Ok(date) = NaiveDate::parse_from_str(&text, "%Y-%m-%d") {
+        return Ok(date);
+    }
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let parsed = match words.as_slice() {
+        ["today"] => Some(today),
+        ["yesterday"] => Some(today - Duration::days(1)),
+        ["this", "week"] => Some(start_of_week(today)),
+        ["this", "month"] => today.with_day(1),
+        ["this", "year"] => NaiveDate::from_ymd_opt(today.year(), 1, 1),
+        ["last", "week"] => Some(start_of_week(today) - Duration::weeks(1)),
+        ["last", "month"] => today.with_day(1).and_then(|first| first.checked_sub_months(Months::new(1))),
+        ["last", "year"] => NaiveDate::from_ymd_opt(today.year() - 1, 1, 1),
+        [day] | ["this", day] => {
+            weekday(day).map(|day| start_of_week(today) + Duration::days(day.num_days_from_monday() as i64))
+        }
+        ["last", day] => weekday(day).map(|day| {
+            let back = (today.weekday().days_since(day) + 6) % 7 + 1;
+            today - Duration::days(back as i64)
+        }),
+        [n, unit, "ago"] => n.parse().ok().and_then(|n| ago(today, n, unit)),
+        _ => None,
+    };
+
+    match parsed.or_else(|| month_day(&text, today)) {
+        Some(date) => Ok(date),
+        None => anyhow::bail!(
+            "Could not understand the date '{}'. Try 2026-09-30, 'sep 30', 'last monday' or '3 days ago'",
+            input.trim()
+        ),
+    }
+}
+
+/// Parse a lower bound: the first moment of the given day
+pub fn parse_since(input: &str) -> Result<DateTime<Utc>> {
+    Ok(start_of_day(parse_date(input, Local::now().date_naive())?))
+}
+
+/// Parse an upper bound: the end of the given day, so the day itself is included
+pub fn parse_until(input: &str) -> Result<DateTime<Utc>> {
+    Ok(start_of_day(parse_date(input, Local::now().date_naive())? + Duration::days(1)))
+}
+
+/// Parse a date range typed as `since..until`, `since..`, `..until` or a lone `since`
+pub fn parse_range(input: &str) -> Result<DateRange> {
+    let (since, until) = input.split_once("..").unwrap_or((input, ""));
+    Ok(DateRange {
+        since: Some(since.trim()).filter(|s| !s.is_empty()).map(parse_since).transpose()?,
+        until: Some(until.trim()).filter(|s| !s.is_empty()).map(parse_until).transpose()?,
+    })
+}
+
+/// Local midnight at the start of a day, as UTC
+pub fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .map(|local| local.with_timezone(&Utc))
+        .unwrap_or_default()
+}
+
+fn start_of_week(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+fn weekday(word: &str) -> Option<Weekday> {
+    word.parse().ok()
+}
+
+fn ago(today: NaiveDate, n: u32, unit: &str) -> Option<NaiveDate> {
+    match unit.trim_end_matches('s') {
+        "day" => today.checked_sub_signed(Duration::days(n as i64)),
+        "week" => today.checked_sub_signed(Duration::weeks(n as i64)),
+        "month" => today.checked_sub_months(Months::new(n)),
+        "year" => today.checked_sub_months(Months::new(n.checked_mul(12)?)),
+        _ => None,
+    }
+}
+
+/// `sep 30`, `30 sep`, `september 30, 2026`. Without a year, the most recent
+/// such date up to today is meant.
+fn month_day(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let cleaned = text.replace(',', " ");
+    let words: Vec<&str> = cleaned.split_whitespace().collect();
+
+    let (first, second, year) = match words.as_slice() {
+        [first, second] => (*first, *second, None),
+        [first, second, year] => (*first, *second, Some(year.parse::<i32>().ok()?)),
+        _ => return None,
+    };
+    let (month, day) = match (month(first), month(second)) {
+        (Some(month), None) => (month, second.parse().ok()?),
+        (None, Some(month)) => (month, first.parse().ok()?),
+        _ => return None,
+    };
+
+    match year {
+        Some(year) => NaiveDate::from_ymd_opt(year, month, day),
+        None => NaiveDate::from_ymd_opt(today.year(), month, day)
+            .filter(|date| *date <= today)
+            .or_else(|| NaiveDate::from_ymd_opt(today.year() - 1, month, day)),
+    }
+}
+
+/// A month from its name or an abbreviation of at least three letters
+fn month(word: &str) -> Option<u32> {
+    let word = word.trim_end_matches('.');
+    if word.len() < 3 {
+        return None;
+    }
+    MONTH_NAMES
+        .iter()
+        .position(|name| name.starts_with(word))
+        .map(|index| index as u32 + 1)
+}
+
+/// How long ago a moment was, roughly: "just now", "5 minutes ago", "3 days ago"
+pub fn time_ago(time: DateTime<Utc>) -> String {
+    let elapsed = Utc::now() - time;
+    let (count, unit) = match elapsed.num_minutes() {
+        ..=0 => return "just now".to_string(),
+        minutes @ 1..=59 => (minutes, "minute"),
+        _ if elapsed.num_hours() < 24 => (elapsed.num_hours(), "hour"),
+        _ => (elapsed.num_days(), "day"),
+    };
+    format!("{} {}{} ago", count, unit, if count == 1 { "" } else { "s" })
+}
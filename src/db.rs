@@ -1,72 +1,286 @@
-use crate::models::{Bucket, Cookie};
+pub mod migrations;
+pub mod stats;
+
+use crate::config::{self, settings};
+use crate::error::{BucketRef, CookieJarError};
+use crate::models::{Bucket, BucketStats, Cookie, CookieWithBucket, SearchResult, Tag, MATCH_END, MATCH_START};
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use libsql::Builder;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Where the jar's data lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageMode {
+    /// A plain libsql file on disk with no remote
+    Local,
+    /// A local replica kept in sync with Turso Cloud
+    Remote,
+    /// A Turso-backed jar opened read-only from its local replica, because Turso
+    /// couldn't be reached when cj started
+    Offline,
+}
 
+/// First wait before retrying a failed sync; it doubles with each failure
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+/// Longest wait between retries
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+#[derive(Clone)]
 pub struct Database {
-    db: libsql::Database,
+    db: Arc<libsql::Database>,
+    mode: StorageMode,
+    /// Turso URL and token, kept to check whether an offline jar can reach Turso again
+    remote: Option<(String, String)>,
+    status: Arc<Mutex<SyncStatus>>,
+}
+
+/// What a sync pulled down from Turso
+#[derive(Debug, Clone, Copy)]
+pub struct SyncReport {
+    /// The replication frame the replica is now at, if it has any
+    pub frame_no: Option<u64>,
+    /// How many frames this sync applied
+    pub frames_synced: usize,
+}
+
+/// How syncing with Turso has been going
+#[derive(Debug, Clone, Default)]
+pub struct SyncStatus {
+    /// The last successful sync, remembered across runs
+    pub last_synced: Option<DateTime<Utc>>,
+    /// Why the last attempt failed, while a sync is waiting to be retried
+    pub pending: Option<String>,
+    /// Set when an offline jar finds Turso reachable again and can be reopened
+    pub reachable: bool,
 }
 
 impl Database {
+    /// Opens the jar at `local_path`, syncing with Turso when credentials are available.
+    /// Falls back to local-only storage when TURSO_DATABASE_URL or TURSO_AUTH_TOKEN is
+    /// missing, or when `force_local` is set. If Turso can't be reached but a replica
+    /// from an earlier run exists, that is opened read-only instead.
+    pub async fn new(local_path: PathBuf, force_local: bool) -> Result<Self> {
+        match remote_credentials() {
+            Some((url, token)) if !force_local => {
+                let has_replica = local_path.exists();
+                match Self::new_remote_replica(local_path.clone(), url.clone(), token.clone()).await {
+                    Err(e) if has_replica => Self::new_offline(local_path, url, token, e).await,
+                    result => result,
+                }
+            }
+            _ => Self::new_local(local_path).await,
+        }
+    }
+
     /// Creates a new Database instance with local replica and Turso sync
-    /// The local database will be stored in $HOME/.cookiejar/cookiejar.db
-    pub async fn new(local_path: PathBuf) -> Result<Self> {
-        let url = std::env::var("TURSO_DATABASE_URL")
-            .context("TURSO_DATABASE_URL must be set in .env file")?;
-        let token = std::env::var("TURSO_AUTH_TOKEN")
-            .context("TURSO_AUTH_TOKEN must be set in .env file")?;
-
-        let db = Builder::new_remote_replica(local_path, url, token)
-            .sync_interval(Duration::from_secs(60)) // Auto-sync every 60 seconds
+    /// The local database will be stored in $HOME/.cookie_jar/cookie_jar.db
+    /// Syncing is up to the caller, see `sync` and `spawn_background_sync`.
+    pub async fn new_remote_replica(local_path: PathBuf, url: String, token: String) -> Result<Self> {
+        let db = Builder::new_remote_replica(local_path, url.clone(), token.clone())
             .build()
             .await
             .context("Failed to create database")?;
 
-        Ok(Self { db })
+        Ok(Self::with_mode(db, StorageMode::Remote, Some((url, token)), None))
+    }
+
+    /// Creates a new Database instance backed only by a local file, without Turso
+    pub async fn new_local(local_path: PathBuf) -> Result<Self> {
+        let db = Builder::new_local(local_path)
+            .build()
+            .await
+            .context("Failed to open local database")?;
+
+        Ok(Self::with_mode(db, StorageMode::Local, None, None))
+    }
+
+    /// Opens an existing replica read-only, so it can still be browsed while Turso is
+    /// unreachable without writing anything the replica would later disagree with
+    async fn new_offline(local_path: PathBuf, url: String, token: String, error: anyhow::Error) -> Result<Self> {
+        let db = Builder::new_local(local_path)
+            .flags(libsql::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .build()
+            .await
+            .context("Failed to open the local copy of the jar")?;
+
+        Ok(Self::with_mode(db, StorageMode::Offline, Some((url, token)), Some(format!("{:#}", error))))
+    }
+
+    fn with_mode(db: libsql::Database, mode: StorageMode, remote: Option<(String, String)>, pending: Option<String>) -> Self {
+        let last_synced = match mode {
+            StorageMode::Local => None,
+            StorageMode::Remote | StorageMode::Offline => config::load_last_sync(),
+        };
+
+        Self {
+            db: Arc::new(db),
+            mode,
+            remote,
+            status: Arc::new(Mutex::new(SyncStatus {
+                last_synced,
+                pending,
+                reachable: false,
+            })),
+        }
+    }
+
+    pub fn mode(&self) -> StorageMode {
+        self.mode
     }
 
     pub fn connect(&self) -> Result<libsql::Connection> {
         self.db.connect().context("Failed to connect to database")
     }
 
-    pub async fn sync(&self) -> Result<()> {
-        self.db.sync().await.context("Failed to sync with remote")?;
+    /// A snapshot of how syncing has been going
+    pub fn sync_status(&self) -> SyncStatus {
+        self.status.lock().map(|status| status.clone()).unwrap_or_default()
+    }
+
+    fn update_status(&self, update: impl FnOnce(&mut SyncStatus)) {
+        if let Ok(mut status) = self.status.lock() {
+            update(&mut status);
+        }
+    }
+
+    /// Sync with the remote. Does nothing, and returns None, in local-only mode.
+    /// Failures are recorded as a pending sync until a later sync succeeds.
+    pub async fn sync(&self) -> Result<Option<SyncReport>> {
+        match self.mode {
+            StorageMode::Local => return Ok(None),
+            StorageMode::Offline => {
+                return Err(CookieJarError::RemoteUnreachable(
+                    "the jar was opened read-only from its local copy. Try again once you're back online.".to_string(),
+                )
+                .into());
+            }
+            StorageMode::Remote => {}
+        }
+
+        match self.db.sync().await {
+            Ok(replicated) => {
+                let now = Utc::now();
+                self.update_status(|status| {
+                    status.last_synced = Some(now);
+                    status.pending = None;
+                });
+                config::save_last_sync(now)?;
+
+                Ok(Some(SyncReport {
+                    frame_no: replicated.frame_no(),
+                    frames_synced: replicated.frames_synced(),
+                }))
+            }
+            Err(e) => {
+                self.update_status(|status| status.pending = Some(e.to_string()));
+                Err(CookieJarError::RemoteUnreachable(e.to_string()).into())
+            }
+        }
+    }
+
+    /// Check whether an offline jar can reach Turso again
+    async fn probe_remote(&self) -> Result<()> {
+        let Some((url, token)) = self.remote.clone() else {
+            return Ok(());
+        };
+
+        check_remote(&url, &token).await?;
+        self.update_status(|status| status.reachable = true);
         Ok(())
     }
+
+    /// Sync every `interval` in a background task until the returned handle is aborted,
+    /// so syncing never holds up the UI. Failed syncs are retried sooner, backing off
+    /// exponentially. An offline jar instead checks whether Turso is reachable again.
+    /// Returns None in local-only mode.
+    pub fn spawn_background_sync(&self, interval: Duration) -> Option<tokio::task::JoinHandle<()>> {
+        if self.mode == StorageMode::Local {
+            return None;
+        }
+
+        let db = self.clone();
+        Some(tokio::spawn(async move {
+            let mut failures = u32::from(db.sync_status().pending.is_some());
+            loop {
+                tokio::time::sleep(retry_delay(failures, interval)).await;
+
+                let succeeded = match db.mode {
+                    StorageMode::Offline => db.probe_remote().await.is_ok(),
+                    _ => db.sync().await.is_ok(),
+                };
+
+                match (succeeded, db.mode) {
+                    // Nothing more to do here until the caller reopens the jar
+                    (true, StorageMode::Offline) => return,
+                    (true, _) => failures = 0,
+                    (false, _) => failures += 1,
+                }
+            }
+        }))
+    }
 }
 
-/// Initialize the database schema (buckets and cookies tables)
+/// How long to wait before the next sync: the regular interval, or after failures
+/// an exponentially growing delay
+fn retry_delay(failures: u32, interval: Duration) -> Duration {
+    match failures {
+        0 => interval,
+        n => RETRY_DELAY
+            .saturating_mul(2u32.saturating_pow(n - 1))
+            .min(MAX_RETRY_DELAY),
+    }
+}
+
+/// Check that a Turso database can be reached and the token is accepted
+pub async fn check_remote(url: &str, token: &str) -> Result<()> {
+    let query = async {
+        let remote = Builder::new_remote(url.to_string(), token.to_string()).build().await?;
+        remote.connect()?.query("SELECT 1", ()).await?;
+        Ok::<_, libsql::Error>(())
+    };
+    query
+        .await
+        .map_err(|e| CookieJarError::RemoteUnreachable(e.to_string()).into())
+}
+
+/// The Turso URL (from the environment or config.toml) and auth token, if both are set
+pub fn remote_credentials() -> Option<(String, String)> {
+    Some((settings::current().turso_url.clone()?, env_var("TURSO_AUTH_TOKEN")?))
+}
+
+/// Read an environment variable, treating an empty value as unset
+fn env_var(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|value| !value.trim().is_empty())
+}
+
+/// Bring the database schema up to date by applying any pending migrations
 pub async fn init_schema(conn: &libsql::Connection) -> Result<()> {
-    // Create buckets table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS buckets (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT UNIQUE NOT NULL,
-            created_at INTEGER NOT NULL
-        )",
-        (),
-    )
-    .await
-    .context("Failed to create buckets table")?;
+    let applied = migrations::migrate(conn).await?;
 
-    // Create cookies table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS cookies (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            bucket_id INTEGER NOT NULL,
-            content TEXT NOT NULL CHECK(length(content) <= 300),
-            created_at INTEGER NOT NULL,
-            FOREIGN KEY (bucket_id) REFERENCES buckets(id)
-        )",
-        (),
-    )
-    .await
-    .context("Failed to create cookies table")?;
+    // Tags are parsed in Rust, so cookies that predate the tags tables are tagged here
+    if applied.iter().any(|m| m.version == migrations::TAGS_VERSION) {
+        for cookie in get_all_cookies(conn).await? {
+            set_cookie_tags(conn, cookie.id, &Tag::parse_hashtags(&cookie.content)).await?;
+        }
+    }
 
     Ok(())
 }
 
+/// A failed write, reported as the `CookieJarError` it was recognised as if any,
+/// otherwise as libsql's own error with `context`
+fn write_failed(recognised: Option<CookieJarError>, e: libsql::Error, context: String) -> anyhow::Error {
+    match recognised {
+        Some(error) => error.into(),
+        None => anyhow::Error::new(e).context(context),
+    }
+}
+
 // ============ BUCKET OPERATIONS ============
 
 /// Create a new bucket
@@ -78,7 +292,7 @@ pub async fn create_bucket(conn: &libsql::Connection, name: &str) -> Result<Buck
         libsql::params![name, timestamp],
     )
     .await
-    .context("Failed to create bucket")?;
+    .map_err(|e| write_failed(CookieJarError::duplicate_bucket(&e, name), e, "Failed to create bucket".to_string()))?;
 
     // Get the last inserted row ID
     let mut rows = conn.query("SELECT last_insert_rowid()", ()).await?;
@@ -108,6 +322,26 @@ pub async fn get_all_buckets(conn: &libsql::Connection) -> Result<Vec<Bucket>> {
     Ok(buckets)
 }
 
+/// Get a bucket by its exact name
+pub async fn get_bucket_by_name(conn: &libsql::Connection, name: &str) -> Result<Option<Bucket>> {
+    let mut rows = conn
+        .query(
+            "SELECT id, name, created_at FROM buckets WHERE name = ?1",
+            libsql::params![name],
+        )
+        .await
+        .context("Failed to query bucket")?;
+
+    if let Some(row) = rows.next().await? {
+        let id: i64 = row.get(0)?;
+        let name: String = row.get(1)?;
+        let created_at: i64 = row.get(2)?;
+        Ok(Some(Bucket::new(id, name, created_at)))
+    } else {
+        Ok(None)
+    }
+}
+
 /// Count cookies in a bucket
 pub async fn count_cookies_in_bucket(conn: &libsql::Connection, bucket_id: i64) -> Result<i64> {
     let mut rows = conn
@@ -125,31 +359,209 @@ pub async fn count_cookies_in_bucket(conn: &libsql::Connection, bucket_id: i64)
     }
 }
 
+/// Get all buckets with their cookie counts and the times of their newest and
+/// oldest cookies, in a single query
+pub async fn get_buckets_with_stats(conn: &libsql::Connection) -> Result<Vec<BucketStats>> {
+    let mut rows = conn
+        .query(
+            "SELECT b.id, b.name, b.created_at, COUNT(c.id), MAX(c.created_at), MIN(c.created_at)
+             FROM buckets b
+             LEFT JOIN cookies c ON c.bucket_id = b.id
+             GROUP BY b.id
+             ORDER BY b.name",
+            (),
+        )
+        .await
+        .context("Failed to query buckets")?;
+
+    let mut buckets = Vec::new();
+    while let Some(row) = rows.next().await? {
+        let id: i64 = row.get(0)?;
+        let name: String = row.get(1)?;
+        let created_at: i64 = row.get(2)?;
+        let newest: Option<i64> = row.get(4)?;
+        let oldest: Option<i64> = row.get(5)?;
+        buckets.push(BucketStats {
+            bucket: Bucket::new(id, name, created_at),
+            cookie_count: row.get(3)?,
+            newest_cookie_at: newest.and_then(|t| DateTime::from_timestamp(t, 0)),
+            oldest_cookie_at: oldest.and_then(|t| DateTime::from_timestamp(t, 0)),
+        });
+    }
+
+    Ok(buckets)
+}
+
+/// Rename a bucket
+pub async fn rename_bucket(conn: &libsql::Connection, id: i64, new_name: &str) -> Result<()> {
+    let updated = conn
+        .execute(
+            "UPDATE buckets SET name = ?1 WHERE id = ?2",
+            libsql::params![new_name, id],
+        )
+        .await
+        .map_err(|e| {
+            let context = format!("Failed to rename bucket to \"{}\"", new_name);
+            write_failed(CookieJarError::duplicate_bucket(&e, new_name), e, context)
+        })?;
+
+    if updated == 0 {
+        return Err(CookieJarError::BucketNotFound(BucketRef::Id(id)).into());
+    }
+    Ok(())
+}
+
+/// Move every cookie from one bucket into another and remove the emptied bucket.
+/// Returns the number of cookies that were moved.
+pub async fn merge_buckets(conn: &libsql::Connection, from_id: i64, into_id: i64) -> Result<u64> {
+    if from_id == into_id {
+        anyhow::bail!("Cannot merge a bucket into itself");
+    }
+
+    let tx = conn.transaction().await.context("Failed to start transaction")?;
+    ensure_bucket_exists(&tx, from_id).await?;
+    ensure_bucket_exists(&tx, into_id).await?;
+
+    let moved = tx
+        .execute(
+            "UPDATE cookies SET bucket_id = ?1 WHERE bucket_id = ?2",
+            libsql::params![into_id, from_id],
+        )
+        .await
+        .context("Failed to move cookies")?;
+
+    tx.execute("DELETE FROM buckets WHERE id = ?1", libsql::params![from_id])
+        .await
+        .context("Failed to delete merged bucket")?;
+
+    tx.commit().await.context("Failed to commit bucket merge")?;
+
+    Ok(moved)
+}
+
+/// Fail with `BucketNotFound` unless there's a bucket with this ID
+async fn ensure_bucket_exists(conn: &libsql::Connection, id: i64) -> Result<()> {
+    let mut rows = conn
+        .query("SELECT 1 FROM buckets WHERE id = ?1", libsql::params![id])
+        .await
+        .context("Failed to look up bucket")?;
+    if rows.next().await?.is_none() {
+        return Err(CookieJarError::BucketNotFound(BucketRef::Id(id)).into());
+    }
+    Ok(())
+}
+
+/// What to do with the cookies in a bucket that is being deleted
+#[derive(Debug, Clone, Copy)]
+pub enum BucketCookies {
+    /// Delete the cookies along with the bucket
+    Delete,
+    /// Re-home the cookies in the bucket with this ID
+    MoveTo(i64),
+}
+
+/// Delete a bucket, either deleting or re-homing the cookies it contains
+pub async fn delete_bucket(conn: &libsql::Connection, id: i64, cookies: BucketCookies) -> Result<()> {
+    let tx = conn.transaction().await.context("Failed to start transaction")?;
+
+    match cookies {
+        BucketCookies::Delete => {
+            tx.execute("DELETE FROM cookies WHERE bucket_id = ?1", libsql::params![id])
+                .await
+                .context("Failed to delete cookies in bucket")?;
+        }
+        BucketCookies::MoveTo(target_id) => {
+            if target_id == id {
+                anyhow::bail!("Cannot move cookies into the bucket being deleted");
+            }
+            ensure_bucket_exists(&tx, target_id).await?;
+            tx.execute(
+                "UPDATE cookies SET bucket_id = ?1 WHERE bucket_id = ?2",
+                libsql::params![target_id, id],
+            )
+            .await
+            .context("Failed to move cookies")?;
+        }
+    }
+
+    let deleted = tx
+        .execute("DELETE FROM buckets WHERE id = ?1", libsql::params![id])
+        .await
+        .context("Failed to delete bucket")?;
+
+    if deleted == 0 {
+        return Err(CookieJarError::BucketNotFound(BucketRef::Id(id)).into());
+    }
+
+    tx.commit().await.context("Failed to commit bucket deletion")?;
+
+    Ok(())
+}
+
 // ============ COOKIE OPERATIONS ============
 
+/// How long a cookie is, counted the way people read it: in grapheme clusters, so an
+/// emoji or an accented letter is one character however many code points it takes
+pub fn cookie_length(content: &str) -> usize {
+    content.graphemes(true).count()
+}
+
+/// How many more characters a cookie has room for, negative once it's over the limit
+pub fn cookie_room_left(content: &str) -> isize {
+    settings::current().max_cookie_length as isize - cookie_length(content) as isize
+}
+
+/// Make sure cookie content fits the configured limit before it is written. The schema
+/// can't count characters, so everything that takes a cookie (the menu, the browser,
+/// `cj add` and imports) checks here and nothing else does.
+pub fn validate_cookie_content(content: &str) -> Result<()> {
+    if content.is_empty() {
+        anyhow::bail!("Cookie cannot be empty");
+    }
+    let (length, max) = (cookie_length(content), settings::current().max_cookie_length);
+    if length > max {
+        return Err(CookieJarError::CookieTooLong { length, max }.into());
+    }
+    Ok(())
+}
+
 /// Create a new cookie
 pub async fn create_cookie(conn: &libsql::Connection, bucket_id: i64, content: &str) -> Result<i64> {
-    if content.len() > 300 {
-        anyhow::bail!("Cookie content must be 300 characters or less");
-    }
+    create_cookie_at(conn, bucket_id, content, chrono::Utc::now().timestamp()).await
+}
 
-    let timestamp = chrono::Utc::now().timestamp();
+/// Create a new cookie with an explicit creation timestamp (e.g. when importing)
+pub async fn create_cookie_at(conn: &libsql::Connection, bucket_id: i64, content: &str, timestamp: i64) -> Result<i64> {
+    let tx = conn.transaction().await.context("Failed to start transaction")?;
+    let id = insert_cookie(&tx, bucket_id, content, timestamp).await?;
+    tx.commit().await.context("Failed to commit new cookie")?;
+
+    Ok(id)
+}
+
+/// Create a cookie and its tags without a transaction of its own, for callers that
+/// already hold one (`create_cookie_at` and imports)
+pub async fn insert_cookie(conn: &libsql::Connection, bucket_id: i64, content: &str, timestamp: i64) -> Result<i64> {
+    validate_cookie_content(content)?;
 
     conn.execute(
         "INSERT INTO cookies (bucket_id, content, created_at) VALUES (?1, ?2, ?3)",
         libsql::params![bucket_id, content, timestamp],
     )
     .await
-    .context("Failed to create cookie")?;
+    .map_err(|e| write_failed(CookieJarError::missing_bucket(&e, bucket_id), e, "Failed to create cookie".to_string()))?;
 
     // Get the last inserted row ID
     let mut rows = conn.query("SELECT last_insert_rowid()", ()).await?;
-    if let Some(row) = rows.next().await? {
-        let id: i64 = row.get(0)?;
-        Ok(id)
+    let id: i64 = if let Some(row) = rows.next().await? {
+        row.get(0)?
     } else {
         anyhow::bail!("Failed to get cookie ID after insert")
-    }
+    };
+
+    set_cookie_tags(conn, id, &Tag::parse_hashtags(content)).await?;
+
+    Ok(id)
 }
 
 /// Get all cookies
@@ -174,15 +586,145 @@ pub async fn get_all_cookies(conn: &libsql::Connection) -> Result<Vec<Cookie>> {
     Ok(cookies)
 }
 
-/// Get cookies by bucket ID
-pub async fn get_cookies_by_bucket(conn: &libsql::Connection, bucket_id: i64) -> Result<Vec<Cookie>> {
+/// Which end of the timeline a listing starts from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    #[default]
+    NewestFirst,
+    OldestFirst,
+}
+
+/// A filtered, ordered and paged view of the cookies, built up one option at a time:
+///
+/// `CookieQuery::new().bucket(id).since(start).limit(20)`
+#[derive(Debug, Clone, Default)]
+pub struct CookieQuery {
+    pub bucket_id: Option<i64>,
+    pub tag: Option<String>,
+    /// Inclusive lower bound on `created_at`
+    pub since: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `created_at`
+    pub until: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub order: SortOrder,
+}
+
+impl CookieQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bucket(mut self, bucket_id: Option<i64>) -> Self {
+        self.bucket_id = bucket_id;
+        self
+    }
+
+    pub fn tag(mut self, tag: Option<&str>) -> Self {
+        self.tag = tag.map(normalize_tag);
+        self
+    }
+
+    pub fn since(mut self, since: Option<DateTime<Utc>>) -> Self {
+        self.since = since;
+        self
+    }
+
+    pub fn until(mut self, until: Option<DateTime<Utc>>) -> Self {
+        self.until = until;
+        self
+    }
+
+    pub fn limit(mut self, limit: Option<i64>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn offset(mut self, offset: Option<i64>) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn order(mut self, order: SortOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Whether a cookie falls inside the date range
+    pub fn in_range(&self, cookie: &Cookie) -> bool {
+        self.since.is_none_or(|since| cookie.created_at >= since)
+            && self.until.is_none_or(|until| cookie.created_at < until)
+    }
+}
+
+/// Where the next page of cookies starts: just past the last cookie of the current
+/// page, in `(created_at, id)` order, so pages stay stable while cookies are added
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageCursor {
+    created_at: i64,
+    id: i64,
+}
+
+impl PageCursor {
+    /// Whether a cookie comes after this cursor when listing in `order`
+    pub fn precedes(&self, cookie: &Cookie, order: SortOrder) -> bool {
+        let key = (cookie.created_at.timestamp(), cookie.id);
+        match order {
+            SortOrder::NewestFirst => key < (self.created_at, self.id),
+            SortOrder::OldestFirst => key > (self.created_at, self.id),
+        }
+    }
+}
+
+/// One page of cookies, with the cursor for the page after it if there is one
+#[derive(Debug, Clone)]
+pub struct CookiePage {
+    pub cookies: Vec<CookieWithBucket>,
+    pub next: Option<PageCursor>,
+}
+
+/// Run a query, optionally starting after a cursor, with bucket names joined in
+async fn fetch_cookies(
+    conn: &libsql::Connection,
+    query: &CookieQuery,
+    after: Option<PageCursor>,
+) -> Result<Vec<CookieWithBucket>> {
+    let (direction, past) = match query.order {
+        SortOrder::NewestFirst => ("DESC", "<"),
+        SortOrder::OldestFirst => ("ASC", ">"),
+    };
+
+    // A negative LIMIT means no limit in SQLite
+    let sql = format!(
+        "SELECT c.id, c.bucket_id, c.content, c.created_at, b.name
+         FROM cookies c
+         JOIN buckets b ON b.id = c.bucket_id
+         WHERE (?1 IS NULL OR c.bucket_id = ?1)
+           AND (?2 IS NULL OR c.id IN (
+                SELECT ct.cookie_id FROM cookie_tags ct JOIN tags t ON t.id = ct.tag_id WHERE t.name = ?2))
+           AND (?3 IS NULL OR c.created_at >= ?3)
+           AND (?4 IS NULL OR c.created_at < ?4)
+           AND (?7 IS NULL OR (c.created_at, c.id) {past} (?7, ?8))
+         ORDER BY c.created_at {direction}, c.id {direction}
+         LIMIT ?5 OFFSET ?6"
+    );
+
     let mut rows = conn
         .query(
-            "SELECT id, bucket_id, content, created_at FROM cookies WHERE bucket_id = ?1 ORDER BY created_at DESC",
-            libsql::params![bucket_id],
+            &sql,
+            libsql::params![
+                query.bucket_id,
+                query.tag.clone(),
+                query.since.map(|since| since.timestamp()),
+                query.until.map(|until| until.timestamp()),
+                query.limit.unwrap_or(-1),
+                query.offset.unwrap_or(0),
+                after.map(|cursor| cursor.created_at),
+                after.map(|cursor| cursor.id),
+            ],
         )
         .await
-        .context("Failed to query cookies by bucket")?;
+        .context("Failed to query cookies")?;
 
     let mut cookies = Vec::new();
     while let Some(row) = rows.next().await? {
@@ -190,8 +732,471 @@ pub async fn get_cookies_by_bucket(conn: &libsql::Connection, bucket_id: i64) ->
         let bucket_id: i64 = row.get(1)?;
         let content: String = row.get(2)?;
         let created_at: i64 = row.get(3)?;
-        cookies.push(Cookie::new(id, bucket_id, content, created_at));
+        let bucket_name: String = row.get(4)?;
+        cookies.push(CookieWithBucket {
+            cookie: Cookie::new(id, bucket_id, content, created_at),
+            bucket_name,
+        });
     }
 
     Ok(cookies)
 }
+
+/// Get the cookies matching a query
+pub async fn query_cookies(conn: &libsql::Connection, query: &CookieQuery) -> Result<Vec<Cookie>> {
+    let cookies = fetch_cookies(conn, query, None).await?;
+    Ok(cookies.into_iter().map(|c| c.cookie).collect())
+}
+
+/// Get the cookies matching a query along with their bucket names
+pub async fn query_cookies_with_buckets(conn: &libsql::Connection, query: &CookieQuery) -> Result<Vec<CookieWithBucket>> {
+    fetch_cookies(conn, query, None).await
+}
+
+/// Get one page of the cookies matching a query, starting after `after` (or at the
+/// beginning). The query's own limit and offset are ignored.
+pub async fn get_cookie_page(
+    conn: &libsql::Connection,
+    query: &CookieQuery,
+    after: Option<PageCursor>,
+    page_size: i64,
+) -> Result<CookiePage> {
+    // Ask for one extra row to find out whether there is another page
+    let query = query.clone().limit(Some(page_size + 1)).offset(None);
+    let cookies = fetch_cookies(conn, &query, after).await?;
+    Ok(into_page(cookies, page_size))
+}
+
+/// Turn up to `page_size + 1` cookies into a page, the extra one only showing
+/// that there is a next page
+pub fn into_page(mut cookies: Vec<CookieWithBucket>, page_size: i64) -> CookiePage {
+    let next = if cookies.len() as i64 > page_size {
+        cookies.truncate(page_size as usize);
+        cookies.last().map(|last| PageCursor {
+            created_at: last.cookie.created_at.timestamp(),
+            id: last.cookie.id,
+        })
+    } else {
+        None
+    };
+
+    CookiePage { cookies, next }
+}
+
+/// Get a single cookie by ID
+pub async fn get_cookie(conn: &libsql::Connection, id: i64) -> Result<Option<Cookie>> {
+    let mut rows = conn
+        .query(
+            "SELECT id, bucket_id, content, created_at FROM cookies WHERE id = ?1",
+            libsql::params![id],
+        )
+        .await
+        .context("Failed to query cookie")?;
+
+    if let Some(row) = rows.next().await? {
+        let id: i64 = row.get(0)?;
+        let bucket_id: i64 = row.get(1)?;
+        let content: String = row.get(2)?;
+        let created_at: i64 = row.get(3)?;
+        Ok(Some(Cookie::new(id, bucket_id, content, created_at)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Replace the text of an existing cookie
+pub async fn update_cookie_content(conn: &libsql::Connection, id: i64, content: &str) -> Result<()> {
+    let tx = conn.transaction().await.context("Failed to start transaction")?;
+    write_cookie_content(&tx, id, content).await?;
+    tx.commit().await.context("Failed to commit cookie update")?;
+
+    Ok(())
+}
+
+/// Replace a cookie's text and its tags, inside the caller's transaction
+async fn write_cookie_content(conn: &libsql::Connection, id: i64, content: &str) -> Result<()> {
+    validate_cookie_content(content)?;
+
+    let updated = conn
+        .execute(
+            "UPDATE cookies SET content = ?1 WHERE id = ?2",
+            libsql::params![content, id],
+        )
+        .await
+        .context("Failed to update cookie")?;
+
+    if updated == 0 {
+        anyhow::bail!("Cookie #{} does not exist", id);
+    }
+
+    set_cookie_tags(conn, id, &Tag::parse_hashtags(content)).await?;
+
+    Ok(())
+}
+
+/// Change a cookie's text, its bucket or both in one transaction, so a failed move
+/// doesn't leave the new text behind
+pub async fn edit_cookie(conn: &libsql::Connection, id: i64, content: Option<&str>, bucket_id: Option<i64>) -> Result<()> {
+    let tx = conn.transaction().await.context("Failed to start transaction")?;
+    if let Some(content) = content {
+        write_cookie_content(&tx, id, content).await?;
+    }
+    if let Some(bucket_id) = bucket_id {
+        move_cookie_to_bucket(&tx, id, bucket_id).await?;
+    }
+    tx.commit().await.context("Failed to commit cookie edit")?;
+
+    Ok(())
+}
+
+/// Move a cookie into a different bucket
+pub async fn move_cookie_to_bucket(conn: &libsql::Connection, id: i64, bucket_id: i64) -> Result<()> {
+    let updated = conn
+        .execute(
+            "UPDATE cookies SET bucket_id = ?1 WHERE id = ?2",
+            libsql::params![bucket_id, id],
+        )
+        .await
+        .map_err(|e| write_failed(CookieJarError::missing_bucket(&e, bucket_id), e, "Failed to move cookie".to_string()))?;
+
+    if updated == 0 {
+        anyhow::bail!("Cookie #{} does not exist", id);
+    }
+    Ok(())
+}
+
+/// Delete a cookie
+pub async fn delete_cookie(conn: &libsql::Connection, id: i64) -> Result<()> {
+    let deleted = conn
+        .execute("DELETE FROM cookies WHERE id = ?1", libsql::params![id])
+        .await
+        .context("Failed to delete cookie")?;
+
+    if deleted == 0 {
+        anyhow::bail!("Cookie #{} does not exist", id);
+    }
+    Ok(())
+}
+
+// ============ TAG OPERATIONS ============
+
+/// Accept tag filters with or without the leading '#', in any case
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').to_lowercase()
+}
+
+/// Replace the tags on a cookie, creating any tags that don't exist yet
+pub async fn set_cookie_tags(conn: &libsql::Connection, cookie_id: i64, tags: &[String]) -> Result<()> {
+    conn.execute("DELETE FROM cookie_tags WHERE cookie_id = ?1", libsql::params![cookie_id])
+        .await
+        .context("Failed to clear cookie tags")?;
+
+    for tag in tags {
+        conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", libsql::params![tag.as_str()])
+            .await
+            .context("Failed to create tag")?;
+
+        conn.execute(
+            "INSERT OR IGNORE INTO cookie_tags (cookie_id, tag_id)
+             SELECT ?1, id FROM tags WHERE name = ?2",
+            libsql::params![cookie_id, tag.as_str()],
+        )
+        .await
+        .context("Failed to tag cookie")?;
+    }
+
+    Ok(())
+}
+
+/// Get every tag in use with the number of cookies carrying it, most used first
+pub async fn get_tag_counts(conn: &libsql::Connection) -> Result<Vec<(Tag, i64)>> {
+    let mut rows = conn
+        .query(
+            "SELECT t.id, t.name, COUNT(*) AS uses
+             FROM tags t
+             JOIN cookie_tags ct ON ct.tag_id = t.id
+             GROUP BY t.id
+             ORDER BY uses DESC, t.name",
+            (),
+        )
+        .await
+        .context("Failed to query tags")?;
+
+    let mut tags = Vec::new();
+    while let Some(row) = rows.next().await? {
+        let id: i64 = row.get(0)?;
+        let name: String = row.get(1)?;
+        let count: i64 = row.get(2)?;
+        tags.push((Tag { id, name }, count));
+    }
+
+    Ok(tags)
+}
+
+// ============ SEARCH ============
+
+/// Turn free text into an FTS5 query: every word must match, as a prefix,
+/// and FTS5 operators typed by the user are treated as plain text
+fn fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Full-text search over cookie content, best matches first,
+/// optionally limited to cookies with a given tag
+pub async fn search_cookies(conn: &libsql::Connection, query: &str, tag: Option<&str>) -> Result<Vec<SearchResult>> {
+    let fts_query = fts_query(query);
+    if fts_query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut rows = conn
+        .query(
+            "SELECT c.id, c.bucket_id, c.content, c.created_at,
+                    snippet(cookies_fts, 0, ?2, ?3, '…', 24), b.name
+             FROM cookies_fts
+             JOIN cookies c ON c.id = cookies_fts.rowid
+             JOIN buckets b ON b.id = c.bucket_id
+             WHERE cookies_fts MATCH ?1
+               AND (?4 IS NULL OR c.id IN (
+                   SELECT ct.cookie_id FROM cookie_tags ct
+                   JOIN tags t ON t.id = ct.tag_id
+                   WHERE t.name = ?4
+               ))
+             ORDER BY rank",
+            libsql::params![fts_query, MATCH_START, MATCH_END, tag.map(normalize_tag)],
+        )
+        .await
+        .context("Failed to search cookies")?;
+
+    let mut results = Vec::new();
+    while let Some(row) = rows.next().await? {
+        let id: i64 = row.get(0)?;
+        let bucket_id: i64 = row.get(1)?;
+        let content: String = row.get(2)?;
+        let created_at: i64 = row.get(3)?;
+        let snippet: String = row.get(4)?;
+        let bucket_name: String = row.get(5)?;
+        results.push(SearchResult {
+            cookie: Cookie::new(id, bucket_id, content, created_at),
+            bucket_name,
+            snippet,
+        });
+    }
+
+    Ok(results)
+}
+
+// ============ REACHING INTO THE JAR ============
+
+/// Cookies not seen for this long (or never seen) get the maximum weight
+const MAX_UNSEEN_HOURS: f64 = 24.0 * 30.0;
+
+/// How to pick a cookie when reaching into the jar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DipMode {
+    /// Every cookie is equally likely
+    Uniform,
+    /// Cookies that haven't been shown recently are more likely
+    FavourUnseen,
+}
+
+/// Pull one random cookie out of the jar, optionally from a single bucket.
+/// Returns None if there are no cookies to choose from.
+pub async fn reach_into_jar(conn: &libsql::Connection, bucket_id: Option<i64>, mode: DipMode) -> Result<Option<Cookie>> {
+    if mode == DipMode::Uniform {
+        let mut rows = conn
+            .query(
+                "SELECT id, bucket_id, content, created_at FROM cookies
+                 WHERE ?1 IS NULL OR bucket_id = ?1
+                 ORDER BY random() LIMIT 1",
+                libsql::params![bucket_id],
+            )
+            .await
+            .context("Failed to pick a cookie")?;
+
+        return match rows.next().await? {
+            Some(row) => Ok(Some(Cookie::new(row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))),
+            None => Ok(None),
+        };
+    }
+
+    let mut rows = conn
+        .query(
+            "SELECT c.id, c.bucket_id, c.content, c.created_at, MAX(v.viewed_at)
+             FROM cookies c
+             LEFT JOIN cookie_views v ON v.cookie_id = c.id
+             WHERE ?1 IS NULL OR c.bucket_id = ?1
+             GROUP BY c.id",
+            libsql::params![bucket_id],
+        )
+        .await
+        .context("Failed to pick a cookie")?;
+
+    let mut candidates = Vec::new();
+    while let Some(row) = rows.next().await? {
+        let cookie = Cookie::new(row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?);
+        let last_viewed: Option<i64> = row.get(4)?;
+        candidates.push((cookie, last_viewed));
+    }
+
+    Ok(pick_unseen(candidates))
+}
+
+/// Pick one of the cookies at random, favouring those whose last view (a Unix
+/// timestamp, if they've been shown at all) is longest ago
+pub fn pick_unseen(candidates: Vec<(Cookie, Option<i64>)>) -> Option<Cookie> {
+    let now = chrono::Utc::now().timestamp();
+
+    // Weight grows with the hours since the cookie was last shown
+    let mut candidates: Vec<(Cookie, f64)> = candidates
+        .into_iter()
+        .map(|(cookie, last_viewed)| {
+            let weight = match last_viewed {
+                Some(viewed_at) => ((now - viewed_at).max(0) as f64 / 3600.0).min(MAX_UNSEEN_HOURS) + 1.0,
+                None => MAX_UNSEEN_HOURS + 1.0,
+            };
+            (cookie, weight)
+        })
+        .collect();
+
+    let total: f64 = candidates.iter().map(|(_, weight)| weight).sum();
+    let mut target = rand::random::<f64>() * total;
+
+    for (cookie, weight) in &candidates {
+        if target < *weight {
+            return Some(cookie.clone());
+        }
+        target -= weight;
+    }
+
+    candidates.pop().map(|(cookie, _)| cookie)
+}
+
+/// Remember that a cookie was just shown, so weighted recall favours others next time
+pub async fn record_cookie_view(conn: &libsql::Connection, cookie_id: i64) -> Result<()> {
+    let timestamp = chrono::Utc::now().timestamp();
+
+    conn.execute(
+        "INSERT INTO cookie_views (cookie_id, viewed_at) VALUES (?1, ?2)",
+        libsql::params![cookie_id, timestamp],
+    )
+    .await
+    .context("Failed to record cookie view")?;
+
+    Ok(())
+}
+
+// ============ REMOTE ATTACH ============
+
+/// Counts of what was copied by [`attach_remote`]
+pub struct AttachSummary {
+    pub buckets_created: usize,
+    pub cookies_copied: usize,
+    pub cookies_skipped: usize,
+}
+
+/// Copy every bucket and cookie from a local-only jar into a Turso database.
+/// See [`copy_jar`] for how what's already there is handled.
+pub async fn attach_remote(local_path: PathBuf, url: String, token: String) -> Result<AttachSummary> {
+    let local = Database::new_local(local_path).await?;
+    let local_conn = local.connect()?;
+    init_schema(&local_conn).await?;
+
+    let remote = Builder::new_remote(url, token)
+        .build()
+        .await
+        .context("Failed to connect to Turso")?;
+    let remote_conn = remote.connect().context("Failed to connect to Turso")?;
+    init_schema(&remote_conn).await?;
+
+    copy_jar(&local_conn, &remote_conn).await
+}
+
+/// Copy every bucket and cookie, with its tags and view history, from one jar into another.
+/// Buckets are matched by name, and cookies already present in the other bucket with
+/// the same content are skipped, so copying twice is harmless.
+pub async fn copy_jar(from: &libsql::Connection, into: &libsql::Connection) -> Result<AttachSummary> {
+    let buckets = get_all_buckets(from).await?;
+    let cookies = get_all_cookies(from).await?;
+
+    let mut views: HashMap<i64, Vec<i64>> = HashMap::new();
+    let mut rows = from
+        .query("SELECT cookie_id, viewed_at FROM cookie_views ORDER BY id", ())
+        .await
+        .context("Failed to query cookie views")?;
+    while let Some(row) = rows.next().await? {
+        views.entry(row.get(0)?).or_default().push(row.get(1)?);
+    }
+
+    let mut summary = AttachSummary {
+        buckets_created: 0,
+        cookies_copied: 0,
+        cookies_skipped: 0,
+    };
+
+    let tx = into.transaction().await.context("Failed to start transaction")?;
+
+    // Map bucket IDs in one jar to their counterparts in the other
+    let mut bucket_ids = HashMap::new();
+    for bucket in &buckets {
+        let remote_id = match get_bucket_by_name(&tx, &bucket.name).await? {
+            Some(existing) => existing.id,
+            None => {
+                tx.execute(
+                    "INSERT INTO buckets (name, created_at) VALUES (?1, ?2)",
+                    libsql::params![bucket.name.as_str(), bucket.created_at.timestamp()],
+                )
+                .await
+                .context("Failed to copy bucket")?;
+                summary.buckets_created += 1;
+                tx.last_insert_rowid()
+            }
+        };
+        bucket_ids.insert(bucket.id, remote_id);
+    }
+
+    for cookie in &cookies {
+        let Some(&bucket_id) = bucket_ids.get(&cookie.bucket_id) else {
+            summary.cookies_skipped += 1;
+            continue;
+        };
+
+        let mut rows = tx
+            .query(
+                "SELECT 1 FROM cookies WHERE bucket_id = ?1 AND content = ?2",
+                libsql::params![bucket_id, cookie.content.as_str()],
+            )
+            .await?;
+        if rows.next().await?.is_some() {
+            summary.cookies_skipped += 1;
+            continue;
+        }
+
+        tx.execute(
+            "INSERT INTO cookies (bucket_id, content, created_at) VALUES (?1, ?2, ?3)",
+            libsql::params![bucket_id, cookie.content.as_str(), cookie.created_at.timestamp()],
+        )
+        .await
+        .context("Failed to copy cookie")?;
+        let id = tx.last_insert_rowid();
+        set_cookie_tags(&tx, id, &Tag::parse_hashtags(&cookie.content)).await?;
+
+        for viewed_at in views.get(&cookie.id).into_iter().flatten() {
+            tx.execute(
+                "INSERT INTO cookie_views (cookie_id, viewed_at) VALUES (?1, ?2)",
+                libsql::params![id, *viewed_at],
+            )
+            .await
+            .context("Failed to copy cookie view")?;
+        }
+        summary.cookies_copied += 1;
+    }
+
+    tx.commit().await.context("Failed to commit copied cookies")?;
+
+    Ok(summary)
+}
@@ -0,0 +1,278 @@
+use anyhow::{Context, Result};
+
+/// A numbered, forward-only change to the database schema
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+/// A migration that has already been applied to this database
+pub struct AppliedMigration {
+    pub version: i64,
+    pub description: String,
+    pub applied_at: i64,
+}
+
+/// The migration that added tags, after which `db::init_schema` tags the cookies already in the jar
+pub const TAGS_VERSION: i64 = 4;
+
+/// Every migration, in the order it must be applied. Never edit or reorder an
+/// entry once it has shipped; add a new one instead.
+///
+/// Migrations 1-3 use IF NOT EXISTS because jars created before versioning was
+/// introduced already have those tables.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Create buckets and cookies tables",
+        sql: "CREATE TABLE IF NOT EXISTS buckets (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT UNIQUE NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS cookies (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                bucket_id INTEGER NOT NULL,
+                content TEXT NOT NULL CHECK(length(content) <= 300),
+                created_at INTEGER NOT NULL,
+                FOREIGN KEY (bucket_id) REFERENCES buckets(id)
+            );",
+    },
+    Migration {
+        version: 2,
+        description: "Add full-text search index over cookie content",
+        sql: "CREATE VIRTUAL TABLE IF NOT EXISTS cookies_fts USING fts5(
+                content,
+                content = 'cookies',
+                content_rowid = 'id',
+                tokenize = 'porter unicode61'
+            );
+
+            CREATE TRIGGER IF NOT EXISTS cookies_fts_insert AFTER INSERT ON cookies BEGIN
+                INSERT INTO cookies_fts (rowid, content) VALUES (new.id, new.content);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS cookies_fts_delete AFTER DELETE ON cookies BEGIN
+                INSERT INTO cookies_fts (cookies_fts, rowid, content) VALUES ('delete', old.id, old.content);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS cookies_fts_update AFTER UPDATE OF content ON cookies BEGIN
+                INSERT INTO cookies_fts (cookies_fts, rowid, content) VALUES ('delete', old.id, old.content);
+                INSERT INTO cookies_fts (rowid, content) VALUES (new.id, new.content);
+            END;
+
+            INSERT INTO cookies_fts (cookies_fts) VALUES ('rebuild');",
+    },
+    Migration {
+        version: 3,
+        description: "Track when cookies are pulled out of the jar",
+        sql: "CREATE TABLE IF NOT EXISTS cookie_views (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                cookie_id INTEGER NOT NULL,
+                viewed_at INTEGER NOT NULL,
+                FOREIGN KEY (cookie_id) REFERENCES cookies(id)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_cookie_views_cookie ON cookie_views (cookie_id, viewed_at);
+
+            CREATE TRIGGER IF NOT EXISTS cookie_views_cleanup AFTER DELETE ON cookies BEGIN
+                DELETE FROM cookie_views WHERE cookie_id = old.id;
+            END;",
+    },
+    Migration {
+        version: TAGS_VERSION,
+        description: "Add tags parsed from #hashtags in cookies",
+        sql: "CREATE TABLE tags (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT UNIQUE NOT NULL
+            );
+
+            CREATE TABLE cookie_tags (
+                cookie_id INTEGER NOT NULL,
+                tag_id INTEGER NOT NULL,
+                PRIMARY KEY (cookie_id, tag_id),
+                FOREIGN KEY (cookie_id) REFERENCES cookies(id),
+                FOREIGN KEY (tag_id) REFERENCES tags(id)
+            );
+
+            CREATE INDEX idx_cookie_tags_tag ON cookie_tags (tag_id);
+
+            CREATE TRIGGER cookie_tags_cleanup AFTER DELETE ON cookies BEGIN
+                DELETE FROM cookie_tags WHERE cookie_id = old.id;
+            END;",
+    },
+    Migration {
+        version: 5,
+        description: "Index cookies by creation time for paging",
+        sql: "CREATE INDEX idx_cookies_created ON cookies (created_at, id);
+            CREATE INDEX idx_cookies_bucket_created ON cookies (bucket_id, created_at, id);",
+    },
+    Migration {
+        version: 6,
+        description: "Drop the cookie length check so emoji count as one character",
+        // SQL counts code points, not characters, and the limit is configurable, so no CHECK
+        // can match it. db::validate_cookie_content is the only thing enforcing it.
+        // SQLite can't drop a CHECK in place, so the table is rebuilt. Its indexes and
+        // triggers go with the old table and are recreated as migrations 2-5 left them.
+        // Tags and views are set aside meanwhile, since their foreign keys won't let the
+        // old table be dropped from under them.
+        sql: "CREATE TABLE cookie_tags_saved AS SELECT cookie_id, tag_id FROM cookie_tags;
+            CREATE TABLE cookie_views_saved AS SELECT id, cookie_id, viewed_at FROM cookie_views;
+            DELETE FROM cookie_tags;
+            DELETE FROM cookie_views;
+
+            CREATE TABLE cookies_new (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                bucket_id INTEGER NOT NULL,
+                content TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                FOREIGN KEY (bucket_id) REFERENCES buckets(id)
+            );
+
+            INSERT INTO cookies_new (id, bucket_id, content, created_at)
+                SELECT id, bucket_id, content, created_at FROM cookies;
+            DROP TABLE cookies;
+            ALTER TABLE cookies_new RENAME TO cookies;
+
+            CREATE INDEX idx_cookies_created ON cookies (created_at, id);
+            CREATE INDEX idx_cookies_bucket_created ON cookies (bucket_id, created_at, id);
+
+            CREATE TRIGGER cookies_fts_insert AFTER INSERT ON cookies BEGIN
+                INSERT INTO cookies_fts (rowid, content) VALUES (new.id, new.content);
+            END;
+
+            CREATE TRIGGER cookies_fts_delete AFTER DELETE ON cookies BEGIN
+                INSERT INTO cookies_fts (cookies_fts, rowid, content) VALUES ('delete', old.id, old.content);
+            END;
+
+            CREATE TRIGGER cookies_fts_update AFTER UPDATE OF content ON cookies BEGIN
+                INSERT INTO cookies_fts (cookies_fts, rowid, content) VALUES ('delete', old.id, old.content);
+                INSERT INTO cookies_fts (rowid, content) VALUES (new.id, new.content);
+            END;
+
+            CREATE TRIGGER cookie_views_cleanup AFTER DELETE ON cookies BEGIN
+                DELETE FROM cookie_views WHERE cookie_id = old.id;
+            END;
+
+            CREATE TRIGGER cookie_tags_cleanup AFTER DELETE ON cookies BEGIN
+                DELETE FROM cookie_tags WHERE cookie_id = old.id;
+            END;
+
+            INSERT INTO cookie_tags (cookie_id, tag_id) SELECT cookie_id, tag_id FROM cookie_tags_saved;
+            INSERT INTO cookie_views (id, cookie_id, viewed_at) SELECT id, cookie_id, viewed_at FROM cookie_views_saved;
+            DROP TABLE cookie_tags_saved;
+            DROP TABLE cookie_views_saved;",
+    },
+];
+
+/// The schema version this build of cj expects
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// Create the table that records which migrations have been applied
+async fn ensure_version_table(conn: &libsql::Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at INTEGER NOT NULL
+        )",
+        (),
+    )
+    .await
+    .context("Failed to create schema_version table")?;
+
+    Ok(())
+}
+
+/// The version of the schema in this database (0 for a brand-new or pre-versioning jar)
+pub async fn current_version(conn: &libsql::Connection) -> Result<i64> {
+    ensure_version_table(conn).await?;
+
+    let mut rows = conn
+        .query("SELECT COALESCE(MAX(version), 0) FROM schema_version", ())
+        .await
+        .context("Failed to read schema version")?;
+
+    if let Some(row) = rows.next().await? {
+        let version: i64 = row.get(0)?;
+        Ok(version)
+    } else {
+        Ok(0)
+    }
+}
+
+/// Every migration recorded in this database, oldest first
+pub async fn applied_migrations(conn: &libsql::Connection) -> Result<Vec<AppliedMigration>> {
+    ensure_version_table(conn).await?;
+
+    let mut rows = conn
+        .query(
+            "SELECT version, description, applied_at FROM schema_version ORDER BY version",
+            (),
+        )
+        .await
+        .context("Failed to read applied migrations")?;
+
+    let mut applied = Vec::new();
+    while let Some(row) = rows.next().await? {
+        applied.push(AppliedMigration {
+            version: row.get(0)?,
+            description: row.get(1)?,
+            applied_at: row.get(2)?,
+        });
+    }
+
+    Ok(applied)
+}
+
+/// Refuse to touch a database written by a newer cj, since its schema may
+/// have changed in ways this build doesn't understand
+fn check_not_newer(version: i64) -> Result<()> {
+    if version > latest_version() {
+        anyhow::bail!(
+            "This jar uses schema version {}, but this version of cj only understands up to version {}. Please upgrade cj.",
+            version,
+            latest_version()
+        );
+    }
+    Ok(())
+}
+
+/// Migrations that have not been applied to this database yet
+pub async fn pending(conn: &libsql::Connection) -> Result<Vec<&'static Migration>> {
+    let version = current_version(conn).await?;
+    check_not_newer(version)?;
+
+    Ok(MIGRATIONS.iter().filter(|m| m.version > version).collect())
+}
+
+/// Apply every pending migration, each in its own transaction.
+/// Returns the migrations that were applied.
+pub async fn migrate(conn: &libsql::Connection) -> Result<Vec<&'static Migration>> {
+    let pending = pending(conn).await?;
+
+    for migration in &pending {
+        let tx = conn.transaction().await.context("Failed to start transaction")?;
+
+        tx.execute_batch(migration.sql)
+            .await
+            .context(format!("Failed to apply migration {}: {}", migration.version, migration.description))?;
+
+        tx.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, ?2, ?3)",
+            libsql::params![migration.version, migration.description, chrono::Utc::now().timestamp()],
+        )
+        .await
+        .context("Failed to record migration")?;
+
+        tx.commit()
+            .await
+            .context(format!("Failed to commit migration {}", migration.version))?;
+    }
+
+    Ok(pending)
+}
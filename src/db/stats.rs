@@ -0,0 +1,239 @@
+use crate::models::{Bucket, Cookie};
+use anyhow::{Context, Result};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// How many weeks and months of history the dashboard covers
+const WEEKS: usize = 12;
+const MONTHS: usize = 12;
+
+/// Momentum across the whole jar, all dates in local time
+#[derive(Debug, Clone, Serialize)]
+pub struct JarStats {
+    pub total_cookies: i64,
+    pub total_buckets: i64,
+    /// Consecutive days up to today (or yesterday) with at least one cookie
+    pub current_streak: i64,
+    pub longest_streak: i64,
+    /// The last 12 weeks, oldest first, labelled by the Monday they start on
+    pub per_week: Vec<PeriodCount>,
+    /// The last 12 months, oldest first, labelled YYYY-MM
+    pub per_month: Vec<PeriodCount>,
+    /// Every bucket, most cookies first
+    pub per_bucket: Vec<PeriodCount>,
+    /// Monday through Sunday
+    pub per_weekday: Vec<PeriodCount>,
+    /// Every day with at least one cookie, oldest first, labelled YYYY-MM-DD
+    pub per_day: Vec<PeriodCount>,
+}
+
+/// A count of cookies for a labelled period or group
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodCount {
+    pub label: String,
+    pub count: i64,
+}
+
+/// Cookie counts keyed by local day (YYYY-MM-DD), week (its Monday), month (YYYY-MM)
+/// and weekday (0 for Sunday through 6 for Saturday)
+#[derive(Default)]
+struct Counts {
+    per_day: HashMap<String, i64>,
+    per_week: HashMap<String, i64>,
+    per_month: HashMap<String, i64>,
+    per_weekday: HashMap<String, i64>,
+}
+
+/// Run a `SELECT label, COUNT(*) ...` query into a map
+async fn grouped_counts(conn: &libsql::Connection, sql: &str) -> Result<HashMap<String, i64>> {
+    let mut rows = conn.query(sql, ()).await.context("Failed to compute stats")?;
+
+    let mut counts = HashMap::new();
+    while let Some(row) = rows.next().await? {
+        let label: String = row.get(0)?;
+        let count: i64 = row.get(1)?;
+        counts.insert(label, count);
+    }
+
+    Ok(counts)
+}
+
+/// Compute the statistics dashboard for the jar
+pub async fn compute(conn: &libsql::Connection) -> Result<JarStats> {
+    let mut rows = conn
+        .query("SELECT (SELECT COUNT(*) FROM cookies), (SELECT COUNT(*) FROM buckets)", ())
+        .await
+        .context("Failed to compute stats")?;
+    let (total_cookies, total_buckets) = match rows.next().await? {
+        Some(row) => (row.get::<i64>(0)?, row.get::<i64>(1)?),
+        None => (0, 0),
+    };
+
+    let per_day = grouped_counts(
+        conn,
+        "SELECT date(created_at, 'unixepoch', 'localtime') AS day, COUNT(*)
+         FROM cookies GROUP BY day",
+    )
+    .await?;
+
+    // 'weekday 0' jumps forward to Sunday, so going back six days lands on the week's Monday
+    let per_week = grouped_counts(
+        conn,
+        "SELECT date(created_at, 'unixepoch', 'localtime', 'weekday 0', '-6 days') AS week, COUNT(*)
+         FROM cookies GROUP BY week",
+    )
+    .await?;
+
+    let per_month = grouped_counts(
+        conn,
+        "SELECT strftime('%Y-%m', created_at, 'unixepoch', 'localtime') AS month, COUNT(*)
+         FROM cookies GROUP BY month",
+    )
+    .await?;
+
+    // %w is 0 for Sunday through 6 for Saturday
+    let per_weekday = grouped_counts(
+        conn,
+        "SELECT strftime('%w', created_at, 'unixepoch', 'localtime') AS weekday, COUNT(*)
+         FROM cookies GROUP BY weekday",
+    )
+    .await?;
+
+    let mut rows = conn
+        .query(
+            "SELECT b.name, COUNT(c.id) AS cookies
+             FROM buckets b
+             LEFT JOIN cookies c ON c.bucket_id = b.id
+             GROUP BY b.id
+             ORDER BY cookies DESC, b.name",
+            (),
+        )
+        .await
+        .context("Failed to compute stats")?;
+    let mut per_bucket = Vec::new();
+    while let Some(row) = rows.next().await? {
+        per_bucket.push(PeriodCount {
+            label: row.get(0)?,
+            count: row.get(1)?,
+        });
+    }
+
+    let counts = Counts { per_day, per_week, per_month, per_weekday };
+    Ok(summarize(total_cookies, total_buckets, counts, per_bucket))
+}
+
+/// Compute the statistics dashboard from cookies and buckets already in memory
+pub fn from_cookies(cookies: &[Cookie], buckets: &[Bucket]) -> JarStats {
+    let mut counts = Counts::default();
+    for cookie in cookies {
+        let day = cookie.created_at.with_timezone(&Local).date_naive();
+        let monday = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+        for (map, label) in [
+            (&mut counts.per_day, day.format("%Y-%m-%d").to_string()),
+            (&mut counts.per_week, monday.format("%Y-%m-%d").to_string()),
+            (&mut counts.per_month, day.format("%Y-%m").to_string()),
+            (&mut counts.per_weekday, day.weekday().num_days_from_sunday().to_string()),
+        ] {
+            *map.entry(label).or_insert(0) += 1;
+        }
+    }
+
+    let mut per_bucket: Vec<PeriodCount> = buckets
+        .iter()
+        .map(|bucket| PeriodCount {
+            label: bucket.name.clone(),
+            count: cookies.iter().filter(|cookie| cookie.bucket_id == bucket.id).count() as i64,
+        })
+        .collect();
+    per_bucket.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.label.cmp(&b.label)));
+
+    summarize(cookies.len() as i64, buckets.len() as i64, counts, per_bucket)
+}
+
+/// Fill in the empty weeks and months and work out the streaks
+fn summarize(total_cookies: i64, total_buckets: i64, counts: Counts, per_bucket: Vec<PeriodCount>) -> JarStats {
+    let today = Local::now().date_naive();
+    let Counts { per_day, per_week, per_month, per_weekday } = counts;
+
+    let this_monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let per_week = (0..WEEKS)
+        .rev()
+        .map(|i| {
+            let label = (this_monday - Duration::weeks(i as i64)).format("%Y-%m-%d").to_string();
+            let count = per_week.get(&label).copied().unwrap_or(0);
+            PeriodCount { label, count }
+        })
+        .collect();
+
+    let per_month = (0..MONTHS)
+        .rev()
+        .map(|i| {
+            let months_back = today.year() * 12 + today.month0() as i32 - i as i32;
+            let label = format!("{:04}-{:02}", months_back / 12, months_back % 12 + 1);
+            let count = per_month.get(&label).copied().unwrap_or(0);
+            PeriodCount { label, count }
+        })
+        .collect();
+
+    let per_weekday = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| PeriodCount {
+            label: name.to_string(),
+            count: per_weekday.get(&((i + 1) % 7).to_string()).copied().unwrap_or(0),
+        })
+        .collect();
+
+    let mut days: Vec<NaiveDate> = per_day
+        .keys()
+        .filter_map(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+        .collect();
+    days.sort();
+    let (current_streak, longest_streak) = streaks(&days, today);
+
+    let per_day = days
+        .iter()
+        .map(|day| {
+            let label = day.format("%Y-%m-%d").to_string();
+            let count = per_day.get(&label).copied().unwrap_or(0);
+            PeriodCount { label, count }
+        })
+        .collect();
+
+    JarStats {
+        total_cookies,
+        total_buckets,
+        current_streak,
+        longest_streak,
+        per_week,
+        per_month,
+        per_bucket,
+        per_weekday,
+        per_day,
+    }
+}
+
+/// Current and longest runs of consecutive days in a sorted list of distinct days.
+/// The current streak is still alive if the last cookie was today or yesterday.
+fn streaks(days: &[NaiveDate], today: NaiveDate) -> (i64, i64) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+
+    for &day in days {
+        run = match previous {
+            Some(prev) if day - prev == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    let current = match previous {
+        Some(last) if today - last <= Duration::days(1) => run,
+        _ => 0,
+    };
+
+    (current, longest)
+}
@@ -0,0 +1,77 @@
+use std::fmt;
+
+/// Failures worth handling differently from the rest: offering to reuse a bucket, say,
+/// or exiting with a distinct code. They travel inside `anyhow::Error` like any other
+/// failure, and can be picked back out with `error.downcast_ref::<CookieJarError>()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CookieJarError {
+    /// A bucket with this name already exists
+    BucketExists(String),
+    /// Cookie content is over the length limit
+    CookieTooLong { length: usize, max: usize },
+    /// There's no such bucket
+    BucketNotFound(BucketRef),
+    /// Turso couldn't be reached, for the reason given
+    RemoteUnreachable(String),
+}
+
+/// How a bucket was asked for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BucketRef {
+    Name(String),
+    Id(i64),
+}
+
+impl CookieJarError {
+    /// What `cj` exits with when a command fails this way. Any other failure exits
+    /// with 1, and usage errors with 2.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CookieJarError::BucketExists(_) => 3,
+            CookieJarError::CookieTooLong { .. } => 4,
+            CookieJarError::BucketNotFound(_) => 5,
+            CookieJarError::RemoteUnreachable(_) => 6,
+        }
+    }
+
+    /// Recognise a failed write of a bucket called `name` as the name already being taken
+    pub fn duplicate_bucket(error: &libsql::Error, name: &str) -> Option<Self> {
+        violates(error, "UNIQUE constraint failed: buckets.name").then(|| CookieJarError::BucketExists(name.to_string()))
+    }
+
+    /// Recognise a failed write putting a cookie in bucket `bucket_id` as there being no such
+    /// bucket. Cookies only refer to buckets, so any foreign key failure must be that one.
+    pub fn missing_bucket(error: &libsql::Error, bucket_id: i64) -> Option<Self> {
+        violates(error, "FOREIGN KEY constraint failed").then_some(CookieJarError::BucketNotFound(BucketRef::Id(bucket_id)))
+    }
+}
+
+/// Whether libsql refused a write for breaking `constraint`. SQLite's message is the
+/// only thing that names the constraint, and writes forwarded to Turso keep it too.
+fn violates(error: &libsql::Error, constraint: &str) -> bool {
+    error.to_string().contains(constraint)
+}
+
+impl fmt::Display for CookieJarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CookieJarError::BucketExists(name) => write!(f, "A bucket named \"{}\" already exists", name),
+            CookieJarError::CookieTooLong { length, max } => {
+                write!(f, "Cookie content must be {} characters or less (this one is {})", max, length)
+            }
+            CookieJarError::BucketNotFound(bucket) => write!(f, "Bucket {} does not exist", bucket),
+            CookieJarError::RemoteUnreachable(reason) => write!(f, "Couldn't reach Turso: {}", reason),
+        }
+    }
+}
+
+impl fmt::Display for BucketRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BucketRef::Name(name) => write!(f, "\"{}\"", name),
+            BucketRef::Id(id) => write!(f, "#{}", id),
+        }
+    }
+}
+
+impl std::error::Error for CookieJarError {}
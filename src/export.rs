@@ -0,0 +1,90 @@
+use crate::models::{Bucket, Cookie};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// Output formats supported by `cj export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "menu", derive(clap::ValueEnum))]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Markdown,
+}
+
+/// One cookie as it appears in JSON and CSV exports.
+/// The same layout is read back by `cj import`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CookieRecord {
+    pub id: Option<i64>,
+    pub bucket: String,
+    pub content: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Pair each cookie with its bucket name for export
+pub fn to_records(cookies: &[Cookie], buckets: &[Bucket]) -> Vec<CookieRecord> {
+    cookies
+        .iter()
+        .map(|cookie| CookieRecord {
+            id: Some(cookie.id),
+            bucket: bucket_name(buckets, cookie.bucket_id).to_string(),
+            content: cookie.content.clone(),
+            created_at: Some(cookie.created_at),
+        })
+        .collect()
+}
+
+/// Write cookies in the requested format
+pub fn write_export(out: &mut impl Write, format: ExportFormat, cookies: &[Cookie], buckets: &[Bucket]) -> Result<()> {
+    match format {
+        ExportFormat::Json => write_json(out, &to_records(cookies, buckets)),
+        ExportFormat::Csv => write_csv(out, &to_records(cookies, buckets)),
+        ExportFormat::Markdown => write_markdown(out, cookies, buckets),
+    }
+}
+
+/// A JSON array of cookie records, with UTC timestamps
+fn write_json(out: &mut impl Write, records: &[CookieRecord]) -> Result<()> {
+    serde_json::to_writer_pretty(&mut *out, records)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// CSV with an `id,bucket,content,created_at` header row
+fn write_csv(out: &mut impl Write, records: &[CookieRecord]) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(out);
+    for record in records {
+        writer.serialize(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// A Markdown document with one section per bucket
+fn write_markdown(out: &mut impl Write, cookies: &[Cookie], buckets: &[Bucket]) -> Result<()> {
+    writeln!(out, "# Cookie Jar")?;
+
+    for bucket in buckets {
+        let in_bucket: Vec<&Cookie> = cookies.iter().filter(|c| c.bucket_id == bucket.id).collect();
+        if in_bucket.is_empty() {
+            continue;
+        }
+
+        writeln!(out, "\n## {}\n", bucket.name)?;
+        for cookie in in_bucket {
+            writeln!(out, "- {} — *{}*", cookie.content, cookie.formatted_created_at())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn bucket_name(buckets: &[Bucket], bucket_id: i64) -> &str {
+    buckets
+        .iter()
+        .find(|b| b.id == bucket_id)
+        .map(|b| b.name.as_str())
+        .unwrap_or("Unknown")
+}
@@ -0,0 +1,200 @@
+use crate::db;
+use crate::error::{BucketRef, CookieJarError};
+use crate::export::CookieRecord;
+use crate::models::Bucket;
+use crate::store::CookieStore;
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Input formats supported by `cj import`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "menu", derive(clap::ValueEnum))]
+pub enum ImportFormat {
+    /// The JSON layout written by `cj export --format json`
+    Json,
+    /// The CSV layout written by `cj export --format csv`
+    Csv,
+    /// One cookie per line, with an optional `bucket:` prefix
+    Text,
+}
+
+impl ImportFormat {
+    /// Guess the format from a file extension, falling back to plain text
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase).as_deref() {
+            Some("json") => ImportFormat::Json,
+            Some("csv") => ImportFormat::Csv,
+            _ => ImportFormat::Text,
+        }
+    }
+}
+
+/// A cookie read from an import file, with where it came from for reporting
+pub struct ImportEntry {
+    /// Line number (text), row number (CSV) or array position (JSON), starting at 1
+    pub position: usize,
+    pub record: CookieRecord,
+}
+
+/// What an import would do, worked out before anything is written
+pub struct ImportPlan {
+    pub to_import: Vec<ImportEntry>,
+    pub new_buckets: Vec<String>,
+    pub duplicates: Vec<ImportEntry>,
+    pub invalid: Vec<(ImportEntry, String)>,
+}
+
+/// Parse the contents of an import file. Cookies without a bucket go into `default_bucket`.
+/// `buckets` are the jar's existing buckets, which plain text can name even when the name
+/// wouldn't pass for one otherwise.
+pub fn parse(input: &str, format: ImportFormat, default_bucket: &str, buckets: &[Bucket]) -> Result<Vec<ImportEntry>> {
+    let records: Vec<CookieRecord> = match format {
+        ImportFormat::Json => serde_json::from_str(input).context("Failed to parse JSON import file")?,
+        ImportFormat::Csv => csv::Reader::from_reader(input.as_bytes())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .context("Failed to parse CSV import file")?,
+        ImportFormat::Text => return Ok(parse_text(input, default_bucket, buckets)),
+    };
+
+    Ok(records
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut record)| {
+            if record.bucket.trim().is_empty() {
+                record.bucket = default_bucket.to_string();
+            }
+            ImportEntry { position: i + 1, record }
+        })
+        .collect())
+}
+
+/// One cookie per non-blank line. A leading list bullet is dropped, and
+/// `bucket: text` puts the cookie in that bucket. See [`bucket_prefix`] for which colons count.
+fn parse_text(input: &str, default_bucket: &str, buckets: &[Bucket]) -> Vec<ImportEntry> {
+    input
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line = line.trim();
+            let line = line
+                .strip_prefix("- ")
+                .or_else(|| line.strip_prefix("* "))
+                .unwrap_or(line)
+                .trim();
+            if line.is_empty() {
+                return None;
+            }
+
+            let (bucket, content) = bucket_prefix(line, buckets).unwrap_or((default_bucket, line));
+
+            Some(ImportEntry {
+                position: i + 1,
+                record: CookieRecord {
+                    id: None,
+                    bucket: bucket.to_string(),
+                    content: content.to_string(),
+                    created_at: None,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Split `bucket: text` into its bucket and text. Plenty of cookies have a colon of their own,
+/// so the prefix only counts if it names an existing bucket, or could be a new bucket's name:
+/// a single word, not a number as in a time like 10:30, and not a URL scheme.
+fn bucket_prefix<'a>(line: &'a str, buckets: &[Bucket]) -> Option<(&'a str, &'a str)> {
+    let (prefix, rest) = line.split_once(':')?;
+    let (bucket, content) = (prefix.trim(), rest.trim());
+    if bucket.is_empty() || content.is_empty() {
+        return None;
+    }
+
+    let existing = buckets.iter().any(|b| b.name == bucket);
+    let one_word = !bucket.contains(char::is_whitespace);
+    let number = bucket.chars().all(|c| c.is_ascii_digit());
+    let url = rest.starts_with("//");
+    (existing || (one_word && !number && !url)).then_some((bucket, content))
+}
+
+/// Validate entries and skip cookies that already exist (same bucket and content),
+/// either in the jar or earlier in the same file
+pub async fn plan(store: &impl CookieStore, entries: Vec<ImportEntry>) -> Result<ImportPlan> {
+    let buckets = store.get_all_buckets().await?;
+    let bucket_names: HashMap<i64, &str> = buckets.iter().map(|b| (b.id, b.name.as_str())).collect();
+
+    let mut seen: HashSet<(String, String)> = store
+        .query_cookies(&db::CookieQuery::new())
+        .await?
+        .into_iter()
+        .map(|c| {
+            let bucket = bucket_names.get(&c.bucket_id).copied().unwrap_or_default();
+            (bucket.to_string(), c.content)
+        })
+        .collect();
+
+    let mut plan = ImportPlan {
+        to_import: Vec::new(),
+        new_buckets: Vec::new(),
+        duplicates: Vec::new(),
+        invalid: Vec::new(),
+    };
+
+    for entry in entries {
+        if entry.record.bucket.trim().is_empty() {
+            plan.invalid.push((entry, "Bucket name cannot be empty".to_string()));
+            continue;
+        }
+        if let Err(e) = db::validate_cookie_content(&entry.record.content) {
+            plan.invalid.push((entry, e.to_string()));
+            continue;
+        }
+
+        let key = (entry.record.bucket.clone(), entry.record.content.clone());
+        if !seen.insert(key) {
+            plan.duplicates.push(entry);
+            continue;
+        }
+
+        let bucket = &entry.record.bucket;
+        if !buckets.iter().any(|b| &b.name == bucket) && !plan.new_buckets.contains(bucket) {
+            plan.new_buckets.push(bucket.clone());
+        }
+        plan.to_import.push(entry);
+    }
+
+    Ok(plan)
+}
+
+/// Write a planned import in a single transaction, creating missing buckets
+/// and keeping original timestamps where the file had them
+pub async fn apply(conn: &libsql::Connection, plan: &ImportPlan) -> Result<usize> {
+    let tx = conn.transaction().await.context("Failed to start transaction")?;
+
+    let mut bucket_ids: HashMap<String, i64> = db::get_all_buckets(&tx)
+        .await?
+        .into_iter()
+        .map(|b| (b.name, b.id))
+        .collect();
+
+    for name in &plan.new_buckets {
+        let bucket = db::create_bucket(&tx, name).await?;
+        bucket_ids.insert(bucket.name, bucket.id);
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    for entry in &plan.to_import {
+        let record = &entry.record;
+        let bucket_id = *bucket_ids
+            .get(&record.bucket)
+            .ok_or_else(|| CookieJarError::BucketNotFound(BucketRef::Name(record.bucket.clone())))?;
+        let timestamp = record.created_at.map(|t| t.timestamp()).unwrap_or(now);
+        db::insert_cookie(&tx, bucket_id, &record.content, timestamp).await?;
+    }
+
+    tx.commit().await.context("Failed to commit import")?;
+
+    Ok(plan.to_import.len())
+}
@@ -0,0 +1,47 @@
+//! Cookie Jar: a jar of small wins, kept in libsql and optionally synced with Turso.
+//!
+//! `config` finds the jar and its settings, `db` reads and writes it and `models` are
+//! what comes back. `store` wraps all of that behind `CookieStore`. The `cj` binary is
+//! a front end over these modules; its menu, browser and setup wizard need the `menu`
+//! feature, which is on by default. Turn it off for tools with no terminal UI.
+//!
+//! Settings, including the Turso URL, come from the environment and config.toml. They're
+//! loaded the first time they're needed, but installing them up front reports a broken
+//! config.toml instead of quietly falling back to the defaults:
+//!
+//! ```no_run
+//! use cookie_jar::config::{self, settings::{self, Settings}};
+//! use cookie_jar::db;
+//! use cookie_jar::store::{CookieStore, LibsqlStore};
+//!
+//! # async fn latest() -> anyhow::Result<()> {
+//! // With TURSO_AUTH_TOKEN in the environment, as cj loads it from ~/.cookie_jar/.env
+//! settings::install(Settings::load()?);
+//! let database = db::Database::new(config::get_db_path()?, false).await?;
+//! let store = LibsqlStore::new(database)?;
+//! if let Some(cookie) = store.reach_into_jar(None, db::DipMode::FavourUnseen).await? {
+//!     println!("{}", cookie.content);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod config;
+pub mod dates;
+pub mod db;
+pub mod error;
+pub mod export;
+pub mod import;
+pub mod models;
+pub mod store;
+
+#[cfg(feature = "menu")]
+pub mod cli;
+#[cfg(feature = "menu")]
+pub mod menu;
+#[cfg(feature = "menu")]
+pub mod setup;
+#[cfg(feature = "menu")]
+pub mod tui;
+
+pub use error::CookieJarError;
@@ -1,19 +1,42 @@
-mod config;
-mod db;
-mod menu;
-mod models;
-
 use anyhow::Result;
+use clap::Parser;
+use cookie_jar::error::CookieJarError;
+use cookie_jar::{cli, config, dates, db, menu, setup, store};
 use colored::*;
+use config::settings::{self, Settings, Storage, SyncPolicy, Theme};
 use crossterm::{
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, Clear, ClearType},
     cursor::MoveTo,
 };
 use std::io::stdout;
+use store::{CookieStore, LibsqlStore};
+use std::path::Path;
+use std::process::ExitCode;
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            let code = e.downcast_ref::<CookieJarError>().map_or(1, CookieJarError::exit_code);
+            ExitCode::from(code)
+        }
+    }
+}
+
+async fn run() -> Result<()> {
+    let cli = cli::Cli::parse();
+
+    // Pick the jar first, since every path below depends on it. Jars are managed
+    // before the active one has to exist, so a missing jar can still be switched away from.
+    config::select_jar(cli.jar.as_deref())?;
+    if let Some(cli::Command::Jar { command }) = cli.command {
+        return cli::jar(command).await;
+    }
+    config::ensure_jar_exists()?;
+
     // Ensure .cookiejar directory exists
     config::ensure_cookiejar_dir()?;
 
@@ -21,49 +44,176 @@ async fn main() -> Result<()> {
     let env_path = config::get_env_path()?;
     dotenvy::from_path(&env_path).ok();
 
+    // Settings are managed before they're loaded, so `cj config edit` can fix a broken file
+    if let Some(cli::Command::Config { command }) = cli.command {
+        return cli::config(command);
+    }
+
+    // Walk through setup on the very first run, or when asked to with `cj init`
+    let first_run = cli.command.is_none() && setup::is_first_run()?;
+    if (first_run || matches!(cli.command, Some(cli::Command::Init))) && !setup::run(first_run).await? {
+        return Ok(());
+    }
+
+    let settings = Settings::load()?;
+    if settings.theme == Theme::Plain {
+        colored::control::set_override(false);
+    }
+    let force_local = cli.local || settings.storage == Storage::Local;
+    let sync_policy = settings.sync;
+    settings::install(settings);
+
+    // Attaching moves the local database file, so it has to happen before the jar is opened
+    if let Some(cli::Command::Attach { url, token }) = cli.command {
+        return cli::attach(url, token).await;
+    }
+
     // Get database path
     let db_path = config::get_db_path()?;
 
-    // Create database instance with local replica
-    let database = db::Database::new(db_path).await?;
+    // Create database instance (local replica, or local-only without Turso credentials).
+    // If Turso is unreachable, the replica from the last run is opened read-only.
+    let database = db::Database::new(db_path.clone(), force_local).await?;
 
     // Get a connection
-    let conn = database.connect()?;
+    let store = LibsqlStore::new(database.clone())?;
+
+    // Bring the schema up to date, unless `cj db migrate` is about to manage it explicitly.
+    // An offline jar is read-only, so it keeps the schema it last synced.
+    if !matches!(cli.command, Some(cli::Command::Db { .. })) && database.mode() != db::StorageMode::Offline {
+        db::init_schema(store.conn()).await?;
+    }
 
-    // Initialize schema (creates tables if they don't exist)
-    db::init_schema(&conn).await?;
+    // Initial sync with Turso Cloud (no-op for local-only jars). `cj sync` does its own.
+    // A failure leaves a pending sync behind rather than stopping cj.
+    if matches!(sync_policy, SyncPolicy::Interval(_)) && !matches!(cli.command, Some(cli::Command::Sync)) {
+        let _ = database.sync().await;
+    }
 
-    // Initial sync with Turso Cloud
-    database.sync().await?;
+    // Run a single subcommand and exit, skipping the interactive menu
+    if let Some(command) = cli.command {
+        if !matches!(command, cli::Command::Sync) {
+            warn_if_out_of_sync(&database);
+        }
+        cli::run(command, &store).await?;
+        if sync_policy == SyncPolicy::OnExit {
+            sync_on_exit(&database).await;
+        }
+        return Ok(());
+    }
+    if first_run {
+        setup::finish(&store).await?;
+        dialoguer::Input::<String>::new()
+            .with_prompt("Press Enter to open your jar")
+            .allow_empty(true)
+            .interact_text()?;
+    }
+    drop(store);
 
     // Enter alternate screen buffer (like vim)
     execute!(stdout(), EnterAlternateScreen, Clear(ClearType::All), MoveTo(0, 0))?;
 
-    // Main menu loop
-    let result = async {
-        loop {
-            match menu::show_main_menu(&conn, &database).await {
-                Ok(should_exit) => {
-                    if should_exit {
-                        // Sync one final time before exiting
-                        database.sync().await?;
-                        println!("\n{} Goodbye!", "👋".bright_white());
-                        break;
-                    }
-                    // After each operation, sync with remote
-                    database.sync().await?;
+    let result = run_menu(database, &db_path, force_local, sync_policy).await;
+
+    // Always exit alternate screen buffer, even on error
+    execute!(stdout(), LeaveAlternateScreen)?;
+
+    result
+}
+
+/// The main menu loop. A jar that started offline is reopened between actions once
+/// Turso can be reached again.
+async fn run_menu(database: db::Database, db_path: &Path, force_local: bool, sync_policy: SyncPolicy) -> Result<()> {
+    let mut store = LibsqlStore::new(database)?;
+    let mut background_sync = start_background_sync(store.database(), sync_policy);
+
+    let result = loop {
+        match menu::show_main_menu(&store).await {
+            Ok(true) => {
+                // Sync one final time before exiting
+                if sync_policy != SyncPolicy::Manual {
+                    sync_on_exit(store.database()).await;
                 }
-                Err(e) => {
-                    eprintln!("\n{} Error: {:?}", "✗".bright_red(), e);
-                    // Continue running even if there's an error
+                println!("\n{} Goodbye!", "👋".bright_white());
+                break Ok(());
+            }
+            Ok(false) => {}
+            // Continue running even if there's an error
+            Err(e) => match e.downcast_ref::<CookieJarError>() {
+                Some(error) => eprintln!("\n{} {}", "⚠".bright_yellow(), error),
+                None => eprintln!("\n{} Error: {:?}", "✗".bright_red(), e),
+            },
+        }
+
+        if store.mode() == db::StorageMode::Offline && store.sync_status().reachable {
+            let reopened = db::Database::new(db_path.to_path_buf(), force_local).await;
+            if let Ok(reopened) = reopened
+                && reopened.mode() == db::StorageMode::Remote
+            {
+                if let Some(task) = background_sync.take() {
+                    task.abort();
                 }
+                store = LibsqlStore::new(reopened)?;
+                db::init_schema(store.conn()).await?;
+                let _ = store.sync().await;
+                background_sync = start_background_sync(store.database(), sync_policy);
             }
         }
-        Ok::<(), anyhow::Error>(())
-    }.await;
+    };
 
-    // Always exit alternate screen buffer, even on error
-    execute!(stdout(), LeaveAlternateScreen)?;
+    if let Some(task) = background_sync {
+        task.abort();
+    }
 
     result
 }
+
+/// Keep the replica fresh without making the menu wait on the network
+fn start_background_sync(database: &db::Database, sync_policy: SyncPolicy) -> Option<tokio::task::JoinHandle<()>> {
+    match sync_policy {
+        SyncPolicy::Interval(interval) => database.spawn_background_sync(interval),
+        SyncPolicy::OnExit | SyncPolicy::Manual => None,
+    }
+}
+
+/// Sync before exiting, warning rather than failing when Turso is unreachable
+async fn sync_on_exit(database: &db::Database) {
+    if database.mode() != db::StorageMode::Remote {
+        return;
+    }
+    if let Err(e) = database.sync().await {
+        eprintln!(
+            "{} Couldn't sync with Turso before exiting ({:#}). The local copy will catch up next time.",
+            "⚠".bright_yellow(),
+            e
+        );
+    }
+}
+
+/// Say so when the jar being shown may be behind Turso
+fn warn_if_out_of_sync(database: &db::Database) {
+    let status = database.sync_status();
+    let Some(reason) = status.pending else {
+        return;
+    };
+
+    let last_synced = status
+        .last_synced
+        .map(|time| format!("last synced {}", dates::time_ago(time)))
+        .unwrap_or_else(|| "never synced".to_string());
+
+    match database.mode() {
+        db::StorageMode::Offline => eprintln!(
+            "{} Turso is unreachable, so the jar is read-only for now ({}): {}",
+            "⚠".bright_yellow(),
+            last_synced,
+            reason
+        ),
+        _ => eprintln!(
+            "{} Couldn't sync with Turso, using the local copy ({}): {}",
+            "⚠".bright_yellow(),
+            last_synced,
+            reason
+        ),
+    }
+}
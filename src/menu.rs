@@ -1,18 +1,37 @@
+use crate::config::{self, settings};
+use crate::dates;
 use crate::db;
-use crate::models::Bucket;
+use crate::db::stats::{JarStats, PeriodCount};
+use crate::error::CookieJarError;
+use crate::store::CookieStore;
+use crate::tui;
+use crate::models::{Bucket, BucketStats, Cookie, SearchResult, Tag};
 use anyhow::Result;
+use chrono::{Datelike, Local};
 use colored::*;
-use crossterm::{execute, terminal::{Clear, ClearType}, cursor::MoveTo};
-use dialoguer::{theme::ColorfulTheme, Input, Select};
+use crossterm::{
+    cursor::{MoveTo, MoveToColumn, MoveUp},
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::Print,
+    terminal::{self, Clear, ClearType},
+};
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
 use std::io::{stdout, stdin, Write};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 /// Main menu options
 #[derive(Debug)]
 enum MainMenuOption {
     AddCookie,
-    ViewAllCookies,
-    ViewCookiesByBucket,
+    ReachIntoJar,
+    BrowseCookies,
+    SearchCookies,
+    BrowseTags,
+    Stats,
     ListBuckets,
+    ManageBuckets,
     Exit,
 }
 
@@ -20,14 +39,56 @@ impl std::fmt::Display for MainMenuOption {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             MainMenuOption::AddCookie => write!(f, "Add a new cookie"),
-            MainMenuOption::ViewAllCookies => write!(f, "View all cookies"),
-            MainMenuOption::ViewCookiesByBucket => write!(f, "View cookies by bucket"),
+            MainMenuOption::ReachIntoJar => write!(f, "Reach into the jar"),
+            MainMenuOption::BrowseCookies => write!(f, "Browse cookies"),
+            MainMenuOption::SearchCookies => write!(f, "Search cookies"),
+            MainMenuOption::BrowseTags => write!(f, "Browse tags"),
+            MainMenuOption::Stats => write!(f, "Stats"),
             MainMenuOption::ListBuckets => write!(f, "List all buckets"),
+            MainMenuOption::ManageBuckets => write!(f, "Manage buckets"),
             MainMenuOption::Exit => write!(f, "Exit"),
         }
     }
 }
 
+/// Actions offered underneath a list of cookies
+#[derive(Debug)]
+enum CookieListOption {
+    Back,
+    EditCookie,
+    DeleteCookie,
+}
+
+impl std::fmt::Display for CookieListOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CookieListOption::Back => write!(f, "Back to main menu"),
+            CookieListOption::EditCookie => write!(f, "Edit cookie"),
+            CookieListOption::DeleteCookie => write!(f, "Delete cookie"),
+        }
+    }
+}
+
+/// Actions available in the "Manage buckets" submenu
+#[derive(Debug)]
+enum ManageBucketOption {
+    Rename,
+    Merge,
+    Delete,
+    Back,
+}
+
+impl std::fmt::Display for ManageBucketOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ManageBucketOption::Rename => write!(f, "Rename bucket"),
+            ManageBucketOption::Merge => write!(f, "Merge into another bucket"),
+            ManageBucketOption::Delete => write!(f, "Delete bucket"),
+            ManageBucketOption::Back => write!(f, "Back to main menu"),
+        }
+    }
+}
+
 /// Pastel colors for buckets (cycling through these)
 const PASTEL_COLORS: &[&str] = &[
     "bright cyan",
@@ -38,7 +99,7 @@ const PASTEL_COLORS: &[&str] = &[
 ];
 
 /// Get a consistent pastel color for a bucket based on its ID
-fn get_bucket_color(bucket_id: i64) -> Color {
+pub fn get_bucket_color(bucket_id: i64) -> Color {
     let index = (bucket_id as usize) % PASTEL_COLORS.len();
     match PASTEL_COLORS[index] {
         "bright cyan" => Color::BrightCyan,
@@ -50,6 +111,29 @@ fn get_bucket_color(bucket_id: i64) -> Color {
     }
 }
 
+/// Render a search snippet with the matched terms highlighted
+pub fn highlight_snippet(result: &SearchResult) -> String {
+    result
+        .snippet_parts()
+        .into_iter()
+        .map(|(text, is_match)| {
+            if is_match {
+                text.bright_yellow().bold().underline().to_string()
+            } else {
+                text.bright_white().to_string()
+            }
+        })
+        .collect()
+}
+
+/// Sync a change that has already been saved locally. A failed sync is left pending for
+/// the next one to pick up, so it's a warning rather than a failure of what was asked for.
+pub async fn sync_after_write(store: &impl CookieStore) {
+    if let Err(e) = store.sync().await {
+        eprintln!("{} {:#}. Saved locally; it will sync next time.", "⚠".bright_yellow(), e);
+    }
+}
+
 /// Wait for user to press Enter before continuing
 fn wait_for_enter() -> Result<()> {
     print!("\n{}", "Press Enter to continue...".bright_white());
@@ -59,8 +143,138 @@ fn wait_for_enter() -> Result<()> {
     Ok(())
 }
 
+/// Ask for a cookie's text, counting down the characters left as it's typed.
+/// dialoguer only looks at the input once Enter is pressed, so this reads keys itself.
+/// The cursor moves a character at a time with the arrow keys, and Home and End jump to either end.
+fn input_cookie(prompt: &str, initial: &str) -> Result<String> {
+    let mut content = initial.to_string();
+    // Byte offset of the cursor, always at the start of a character
+    let mut cursor = content.len();
+    let mut error: Option<String> = None;
+    let mut cursor_row = 0;
+
+    terminal::enable_raw_mode()?;
+    let result = loop {
+        cursor_row = match draw_cookie_input(prompt, &content, cursor, error.as_deref(), cursor_row) {
+            Ok(row) => row,
+            Err(e) => break Err(e),
+        };
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e.into()),
+        };
+
+        error = None;
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let previous = content[..cursor].grapheme_indices(true).next_back().map_or(0, |(i, _)| i);
+        let next = cursor + content[cursor..].graphemes(true).next().map_or(0, str::len);
+        match key.code {
+            KeyCode::Enter => match db::validate_cookie_content(&content) {
+                Ok(()) => break Ok(()),
+                Err(e) => error = Some(e.to_string()),
+            },
+            KeyCode::Char('c') if ctrl => break Err(std::io::Error::from(std::io::ErrorKind::Interrupted).into()),
+            KeyCode::Char('u') if ctrl => {
+                content.clear();
+                cursor = 0;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                content.insert(cursor, c);
+                cursor += c.len_utf8();
+            }
+            KeyCode::Backspace => {
+                content.replace_range(previous..cursor, "");
+                cursor = previous;
+            }
+            KeyCode::Delete => content.replace_range(cursor..next, ""),
+            KeyCode::Left => cursor = previous,
+            KeyCode::Right => cursor = next,
+            KeyCode::Home => cursor = 0,
+            KeyCode::End => cursor = content.len(),
+            _ => {}
+        }
+    };
+    terminal::disable_raw_mode()?;
+    result?;
+
+    // Settle the prompt the way dialoguer leaves its own
+    let mut out = stdout();
+    if cursor_row > 0 {
+        queue!(out, MoveUp(cursor_row))?;
+    }
+    queue!(out, MoveToColumn(0), Clear(ClearType::FromCursorDown))?;
+    println!("{} {} {} {}", "✔".bright_green(), prompt.bold(), "·".bright_black(), content.bright_green());
+
+    Ok(content)
+}
+
+/// Redraw the cookie prompt over its last rendering, which left the cursor `cursor_row`
+/// rows below the first. Returns the row the cursor is on now.
+fn draw_cookie_input(prompt: &str, content: &str, cursor: usize, error: Option<&str>, cursor_row: u16) -> Result<u16> {
+    let left = db::cookie_room_left(content);
+    let status = match error {
+        Some(error) => error.to_string(),
+        None if left < 0 => format!("{} over", -left),
+        None => format!("{} left", left),
+    };
+    let before_content = format!("? {} ({}) › ", prompt, status).width();
+    let status = if error.is_some() || left < 0 {
+        status.bright_red()
+    } else {
+        status.bright_black()
+    };
+
+    let columns = terminal::size()?.0.max(1) as usize;
+    let end = before_content + content.width();
+    let position = before_content + content[..cursor].width();
+
+    let mut out = stdout();
+    if cursor_row > 0 {
+        queue!(out, MoveUp(cursor_row))?;
+    }
+    queue!(
+        out,
+        MoveToColumn(0),
+        Clear(ClearType::FromCursorDown),
+        Print(format!(
+            "{} {} ({}) {} {}",
+            "?".bright_yellow().bold(),
+            prompt.bold(),
+            status,
+            "›".bright_black(),
+            content
+        ))
+    )?;
+
+    // Printing left the cursor on the last row, at the end unless it's somewhere earlier
+    let last_row = (end.max(1) - 1) / columns;
+    let mut row = last_row;
+    if position < end {
+        row = position / columns;
+        if last_row > row {
+            queue!(out, MoveUp((last_row - row) as u16))?;
+        }
+        queue!(out, MoveToColumn((position % columns) as u16))?;
+    }
+    out.flush()?;
+
+    Ok(row as u16)
+}
+
+/// Shorten cookie content to a single line for use in a selection list
+fn preview(content: &str, max_chars: usize) -> String {
+    let mut chars = content.chars();
+    let short: String = chars.by_ref().take(max_chars).collect();
+    if chars.next().is_some() {
+        format!("{}…", short)
+    } else {
+        short
+    }
+}
+
 /// Custom theme with vim keybindings
-struct VimTheme;
+pub struct VimTheme;
 
 impl dialoguer::theme::Theme for VimTheme {
     fn format_prompt(&self, f: &mut dyn std::fmt::Write, prompt: &str) -> std::fmt::Result {
@@ -81,8 +295,34 @@ impl dialoguer::theme::Theme for VimTheme {
     }
 }
 
+/// One line under the header saying where the jar lives and how syncing is going
+fn print_sync_status(store: &impl CookieStore) {
+    let status = store.sync_status();
+    let last_synced = status
+        .last_synced
+        .map(|time| format!("last synced {}", dates::time_ago(time)))
+        .unwrap_or_else(|| "never synced".to_string());
+
+    match store.mode() {
+        db::StorageMode::Local => println!("{}", "  local-only jar".bright_black()),
+        db::StorageMode::Offline if status.reachable => println!(
+            "{}",
+            "  ⟳ Turso is reachable again, reconnecting after this step".bright_yellow()
+        ),
+        db::StorageMode::Offline => println!(
+            "{}",
+            format!("  ⚠ offline, read-only · {}", last_synced).bright_yellow()
+        ),
+        db::StorageMode::Remote if status.pending.is_some() => println!(
+            "{}",
+            format!("  ⟳ sync pending, retrying · {}", last_synced).bright_yellow()
+        ),
+        db::StorageMode::Remote => println!("{}", format!("  synced with Turso · {}", last_synced).bright_black()),
+    }
+}
+
 /// Display the main menu and handle user selection
-pub async fn show_main_menu(conn: &libsql::Connection, db: &crate::db::Database) -> Result<bool> {
+pub async fn show_main_menu(store: &impl CookieStore) -> Result<bool> {
     // Clear screen and move cursor to top before showing menu
     execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0))?;
 
@@ -90,6 +330,10 @@ pub async fn show_main_menu(conn: &libsql::Connection, db: &crate::db::Database)
     println!("{}", "║   C O O K I E     ║".bright_white().bold());
     println!("{}", "║      J A R        ║".bright_white().bold());
     println!("{}", "╚═══════════════════╝".bright_white().bold());
+    if config::active_jar() != config::DEFAULT_JAR {
+        println!("  {} {}", "🫙".bright_white(), config::active_jar().bright_cyan().bold());
+    }
+    print_sync_status(store);
     println!();
     println!("{}", "What would you like to do?".bright_white());
     println!();
@@ -98,9 +342,13 @@ pub async fn show_main_menu(conn: &libsql::Connection, db: &crate::db::Database)
 
     let options = vec![
         MainMenuOption::AddCookie,
-        MainMenuOption::ViewAllCookies,
-        MainMenuOption::ViewCookiesByBucket,
+        MainMenuOption::ReachIntoJar,
+        MainMenuOption::BrowseCookies,
+        MainMenuOption::SearchCookies,
+        MainMenuOption::BrowseTags,
+        MainMenuOption::Stats,
         MainMenuOption::ListBuckets,
+        MainMenuOption::ManageBuckets,
         MainMenuOption::Exit,
     ];
 
@@ -110,54 +358,42 @@ pub async fn show_main_menu(conn: &libsql::Connection, db: &crate::db::Database)
         .interact()?;
 
     match options[selection] {
-        MainMenuOption::AddCookie => add_cookie_flow(conn, db).await?,
-        MainMenuOption::ViewAllCookies => view_all_cookies(conn).await?,
-        MainMenuOption::ViewCookiesByBucket => view_cookies_by_bucket_flow(conn).await?,
-        MainMenuOption::ListBuckets => list_buckets(conn).await?,
+        MainMenuOption::AddCookie => add_cookie_flow(store).await?,
+        MainMenuOption::ReachIntoJar => reach_into_jar_flow(store).await?,
+        MainMenuOption::BrowseCookies => tui::browse(store, None).await?,
+        MainMenuOption::SearchCookies => search_cookies_flow(store).await?,
+        MainMenuOption::BrowseTags => browse_tags_flow(store).await?,
+        MainMenuOption::Stats => {
+            print_stats(&store.stats().await?);
+            wait_for_enter()?;
+        }
+        MainMenuOption::ListBuckets => list_buckets(store).await?,
+        MainMenuOption::ManageBuckets => manage_buckets_flow(store).await?,
         MainMenuOption::Exit => return Ok(true), // Signal to exit
     }
 
     Ok(false) // Continue running
 }
 
-
 /// Flow for adding a new cookie
-async fn add_cookie_flow(conn: &libsql::Connection, db: &crate::db::Database) -> Result<()> {
+async fn add_cookie_flow(store: &impl CookieStore) -> Result<()> {
     // Get all existing buckets
-    let buckets = db::get_all_buckets(conn).await?;
+    let buckets = store.get_buckets_with_stats().await?;
 
     let bucket = if buckets.is_empty() {
         // No buckets exist, create first one
         println!("\n{}", "No buckets exist yet. Let's create your first bucket!".bright_yellow());
-        let bucket_name: String = Input::with_theme(&ColorfulTheme::default())
-            .with_prompt("Bucket name")
-            .interact_text()?;
-
-        let bucket = db::create_bucket(conn, &bucket_name).await?;
-        // Sync immediately after bucket creation to ensure foreign key constraints work
-        db.sync().await?;
-        bucket
+        create_bucket_flow(store, "Bucket name").await?
     } else {
         // Show existing buckets + option to create new
-        select_or_create_bucket(conn, db, &buckets).await?
+        select_or_create_bucket(store, &buckets).await?
     };
 
     // Get cookie content
-    let content: String = Input::with_theme(&ColorfulTheme::default())
-        .with_prompt(format!("Enter your cookie (max 300 chars)"))
-        .validate_with(|input: &String| -> Result<(), &str> {
-            if input.is_empty() {
-                Err("Cookie cannot be empty")
-            } else if input.len() > 300 {
-                Err("Cookie must be 300 characters or less")
-            } else {
-                Ok(())
-            }
-        })
-        .interact_text()?;
+    let content = input_cookie("Enter your cookie", "")?;
 
     // Create the cookie
-    db::create_cookie(conn, bucket.id, &content).await?;
+    store.create_cookie(bucket.id, &content).await?;
 
     println!(
         "\n{} Cookie added to \"{}\" bucket!",
@@ -165,76 +401,181 @@ async fn add_cookie_flow(conn: &libsql::Connection, db: &crate::db::Database) ->
         bucket.name.color(get_bucket_color(bucket.id)).bold()
     );
 
+    let tags = Tag::parse_hashtags(&content);
+    if !tags.is_empty() {
+        let tags: Vec<String> = tags.iter().map(|t| format!("#{}", t)).collect();
+        println!("   {} Tagged {}", "🏷".bright_white(), tags.join(" ").bright_magenta());
+    }
+
     Ok(())
 }
 
 /// Select an existing bucket or create a new one
-async fn select_or_create_bucket(conn: &libsql::Connection, db: &crate::db::Database, buckets: &[Bucket]) -> Result<Bucket> {
+async fn select_or_create_bucket(store: &impl CookieStore, buckets: &[BucketStats]) -> Result<Bucket> {
     println!("\n{}", "Available buckets:".bright_white());
     println!("{}", "(use j/k or arrow keys to navigate)".bright_black());
 
-    let mut items: Vec<String> = Vec::new();
-
-    for bucket in buckets {
-        let count = db::count_cookies_in_bucket(conn, bucket.id).await?;
-        let colored_name = bucket.name.color(get_bucket_color(bucket.id)).bold().to_string();
-        items.push(format!("{} ({} cookies)", colored_name, count));
-    }
+    let mut items: Vec<String> = buckets.iter().map(bucket_item).collect();
 
     items.push("+ Create new bucket".bright_green().to_string());
 
+    let default_bucket = settings::current().default_bucket.as_deref();
+    let default = buckets
+        .iter()
+        .position(|stats| Some(stats.bucket.name.as_str()) == default_bucket)
+        .unwrap_or(0);
+
     let selection = Select::with_theme(&VimTheme)
         .items(&items)
-        .default(0)
+        .default(default)
         .interact()?;
 
     if selection == items.len() - 1 {
-        // Create new bucket
+        create_bucket_flow(store, "New bucket name").await
+    } else {
+        // Use existing bucket
+        Ok(buckets[selection].bucket.clone())
+    }
+}
+
+/// Ask for a name and create a bucket with it. If the name is already taken,
+/// offer to use that bucket instead.
+async fn create_bucket_flow(store: &impl CookieStore, prompt: &str) -> Result<Bucket> {
+    loop {
         let bucket_name: String = Input::with_theme(&ColorfulTheme::default())
-            .with_prompt("New bucket name")
+            .with_prompt(prompt)
             .interact_text()?;
 
-        let bucket = db::create_bucket(conn, &bucket_name).await?;
+        let error = match store.create_bucket(&bucket_name).await {
+            Ok(bucket) => {
+                println!(
+                    "{} Created bucket \"{}\"",
+                    "✓".bright_green(),
+                    bucket.name.color(get_bucket_color(bucket.id)).bold()
+                );
+                // Sync immediately after bucket creation to ensure foreign key constraints work
+                sync_after_write(store).await;
+                return Ok(bucket);
+            }
+            Err(e) => e,
+        };
+
+        let Some(CookieJarError::BucketExists(name)) = error.downcast_ref::<CookieJarError>() else {
+            return Err(error);
+        };
+        let reuse = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("There's already a bucket called \"{}\". Use it?", name))
+            .default(true)
+            .interact()?;
+        if reuse && let Some(bucket) = store.get_bucket_by_name(name).await? {
+            return Ok(bucket);
+        }
+    }
+}
+
+/// Flow for pulling random cookies out of the jar
+async fn reach_into_jar_flow(store: &impl CookieStore) -> Result<()> {
+    let buckets = store.get_all_buckets().await?;
+
+    println!("\n{}", "Reach into which part of the jar?".bright_white());
+    println!("{}", "(use j/k or arrow keys to navigate)".bright_black());
+
+    let mut items = vec!["Anywhere".bright_white().bold().to_string()];
+    items.extend(
+        buckets
+            .iter()
+            .map(|b| b.name.color(get_bucket_color(b.id)).bold().to_string()),
+    );
+
+    let selection = Select::with_theme(&VimTheme)
+        .items(&items)
+        .default(0)
+        .interact()?;
+    let bucket_id = selection.checked_sub(1).map(|i| buckets[i].id);
+
+    let modes = ["Favour cookies I haven't seen lately", "Completely random"];
+    let mode = Select::with_theme(&VimTheme)
+        .items(&modes)
+        .default(0)
+        .interact()?;
+    let mode = if mode == 0 {
+        db::DipMode::FavourUnseen
+    } else {
+        db::DipMode::Uniform
+    };
+
+    loop {
+        let Some(cookie) = store.reach_into_jar(bucket_id, mode).await? else {
+            println!("\n{}", "The jar is empty here. Add a cookie first!".bright_yellow());
+            wait_for_enter()?;
+            return Ok(());
+        };
+        store.record_cookie_view(cookie.id).await?;
+
+        let bucket_name = buckets
+            .iter()
+            .find(|b| b.id == cookie.bucket_id)
+            .map(|b| b.name.as_str())
+            .unwrap_or("Unknown");
+
+        println!("\n{}", "─".repeat(60).bright_black());
         println!(
-            "{} Created bucket \"{}\"",
-            "✓".bright_green(),
-            bucket.name.color(get_bucket_color(bucket.id)).bold()
+            "\n{} {}",
+            "🍪".bright_white(),
+            bucket_name.color(get_bucket_color(cookie.bucket_id)).bold()
         );
-        // Sync immediately after bucket creation to ensure foreign key constraints work
-        db.sync().await?;
-        Ok(bucket)
-    } else {
-        // Use existing bucket
-        Ok(buckets[selection].clone())
+        println!("\n   {}", cookie.content.bright_white().bold());
+        println!(
+            "\n   {} {}",
+            "🕒".bright_black(),
+            cookie.formatted_created_at().bright_black()
+        );
+        println!("\n{}", "─".repeat(60).bright_black());
+        println!();
+
+        let again = Select::with_theme(&VimTheme)
+            .items(&["Reach in again", "Back to main menu"])
+            .default(0)
+            .interact()?;
+        if again != 0 {
+            return Ok(());
+        }
     }
 }
 
-/// View all cookies
-async fn view_all_cookies(conn: &libsql::Connection) -> Result<()> {
-    let cookies = db::get_all_cookies(conn).await?;
-    let buckets = db::get_all_buckets(conn).await?;
+/// Flow for full-text searching cookies
+async fn search_cookies_flow(store: &impl CookieStore) -> Result<()> {
+    let query: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Search for")
+        .interact_text()?;
+
+    let results = store.search_cookies(&query, None).await?;
 
-    if cookies.is_empty() {
-        println!("\n{}", "No cookies yet! Add your first one.".bright_yellow());
+    if results.is_empty() {
+        println!(
+            "\n{} No cookies match \"{}\"",
+            "ℹ".bright_yellow(),
+            query.bright_white()
+        );
         wait_for_enter()?;
         return Ok(());
     }
 
-    println!("\n{}", "All Cookies:".bright_white().bold());
+    println!(
+        "\n{} {}",
+        "Cookies matching".bright_white(),
+        format!("\"{}\"", query).bright_white().bold()
+    );
     println!("{}", "─".repeat(60).bright_black());
 
-    for cookie in &cookies {
-        // Find the bucket for this cookie
-        let bucket = buckets.iter().find(|b| b.id == cookie.bucket_id);
-        let bucket_name = bucket.map(|b| b.name.as_str()).unwrap_or("Unknown");
-        let bucket_color = get_bucket_color(cookie.bucket_id);
-
+    for result in &results {
+        let cookie = &result.cookie;
         println!(
             "\n{} {}",
             "📌".bright_white(),
-            bucket_name.color(bucket_color).bold()
+            result.bucket_name.color(get_bucket_color(cookie.bucket_id)).bold()
         );
-        println!("   {}", cookie.content.bright_white());
+        println!("   {}", highlight_snippet(result));
         println!(
             "   {} {}",
             "🕒".bright_black(),
@@ -243,79 +584,264 @@ async fn view_all_cookies(conn: &libsql::Connection) -> Result<()> {
     }
 
     println!("\n{}", "─".repeat(60).bright_black());
-    println!("Total: {} cookies", cookies.len().to_string().bright_cyan().bold());
+    println!("Found: {} cookies", results.len().to_string().bright_cyan().bold());
 
-    wait_for_enter()?;
+    let cookies: Vec<Cookie> = results.into_iter().map(|r| r.cookie).collect();
+    cookie_list_actions(store, &cookies).await?;
 
     Ok(())
 }
 
-/// Flow for viewing cookies by bucket
-async fn view_cookies_by_bucket_flow(conn: &libsql::Connection) -> Result<()> {
-    let buckets = db::get_all_buckets(conn).await?;
+/// Show a tag cloud and let the user open the cookies carrying one of the tags
+async fn browse_tags_flow(store: &impl CookieStore) -> Result<()> {
+    let tags = store.get_tag_counts().await?;
 
-    if buckets.is_empty() {
-        println!("\n{}", "No buckets exist yet!".bright_yellow());
+    if tags.is_empty() {
+        println!(
+            "\n{}",
+            "No tags yet! Add #hashtags to your cookies to tag them.".bright_yellow()
+        );
         wait_for_enter()?;
         return Ok(());
     }
 
-    println!("\n{}", "Select a bucket:".bright_white());
+    println!("\n{}", "Tag Cloud:".bright_white().bold());
+    println!("{}", "─".repeat(60).bright_black());
+    println!();
+
+    // Tags are sorted by count, so the first one is the most used
+    let max_count = tags[0].1;
+    let mut line_width = 0;
+    print!("  ");
+    for (tag, count) in &tags {
+        let label = format!("#{}({})", tag.name, count);
+        if line_width > 0 && line_width + label.chars().count() > 56 {
+            print!("\n  ");
+            line_width = 0;
+        }
+
+        let styled = if *count * 3 >= max_count * 2 {
+            label.bright_magenta().bold()
+        } else if *count * 3 >= max_count {
+            label.bright_cyan()
+        } else {
+            label.bright_black()
+        };
+        print!("{}  ", styled);
+        line_width += label.chars().count() + 2;
+    }
+    println!("\n");
+    println!("{}", "─".repeat(60).bright_black());
+
+    println!("\n{}", "Open a tag:".bright_white());
     println!("{}", "(use j/k or arrow keys to navigate)".bright_black());
 
-    let items: Vec<String> = buckets
+    let mut items: Vec<String> = tags
         .iter()
-        .map(|b| {
-            let colored_name = b.name.color(get_bucket_color(b.id)).bold().to_string();
-            colored_name
-        })
+        .map(|(tag, count)| format!("{} ({} cookies)", format!("#{}", tag.name).bright_magenta().bold(), count))
         .collect();
+    items.push("Back to main menu".bright_black().to_string());
 
     let selection = Select::with_theme(&VimTheme)
         .items(&items)
         .default(0)
         .interact()?;
 
-    let bucket = &buckets[selection];
-    let cookies = db::get_cookies_by_bucket(conn, bucket.id).await?;
+    let Some((tag, count)) = tags.get(selection) else {
+        return Ok(());
+    };
+
+    let heading = format!(
+        "{} {}",
+        "Cookies tagged".bright_white(),
+        format!("#{}", tag.name).bright_magenta().bold()
+    );
+    let query = db::CookieQuery::new().tag(Some(&tag.name));
+    cookie_pages_flow(store, &heading, &query, *count).await
+}
+
+/// Show the cookies matching a query a page at a time, with n/p to turn pages
+async fn cookie_pages_flow(
+    store: &impl CookieStore,
+    heading: &str,
+    query: &db::CookieQuery,
+    total: i64,
+) -> Result<()> {
+    // Each cookie takes four lines, plus seven for the heading and footer
+    let rows = terminal::size().map(|(_, rows)| rows).unwrap_or(24) as i64;
+    let page_size = ((rows - 7) / 4).max(1);
+
+    // The cursor each visited page started from, so p can step back
+    let mut page_starts = vec![None];
+
+    loop {
+        let start = page_starts.last().copied().flatten();
+        let page = store.get_cookie_page(query, start, page_size).await?;
+
+        execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0))?;
+        println!("{}", heading);
+        println!("{}", "─".repeat(60).bright_black());
 
-    if cookies.is_empty() {
+        for entry in &page.cookies {
+            let cookie = &entry.cookie;
+            println!(
+                "\n{} {}",
+                "📌".bright_white(),
+                entry.bucket_name.color(get_bucket_color(cookie.bucket_id)).bold()
+            );
+            println!("   {}", cookie.content.bright_white());
+            println!(
+                "   {} {}",
+                "🕒".bright_black(),
+                cookie.formatted_created_at().bright_black()
+            );
+        }
+
+        let pages = ((total + page_size - 1) / page_size).max(1);
+        println!("\n{}", "─".repeat(60).bright_black());
         println!(
-            "\n{} No cookies in \"{}\" yet!",
-            "ℹ".bright_yellow(),
-            bucket.name.color(get_bucket_color(bucket.id)).bold()
+            "Total: {} cookies  {}",
+            total.to_string().bright_cyan().bold(),
+            format!("(page {} of {})", page_starts.len(), pages).bright_black()
         );
-        wait_for_enter()?;
-        return Ok(());
+
+        let mut keys = Vec::new();
+        if page.next.is_some() {
+            keys.push("n next page");
+        }
+        if page_starts.len() > 1 {
+            keys.push("p previous page");
+        }
+        keys.push("e edit or delete");
+        keys.push("q back");
+        println!("\n{}", keys.join("  ·  ").bright_black());
+
+        match read_key()? {
+            KeyCode::Char('n') | KeyCode::Right if page.next.is_some() => page_starts.push(page.next),
+            KeyCode::Char('p') | KeyCode::Left if page_starts.len() > 1 => {
+                page_starts.pop();
+            }
+            KeyCode::Char('e') | KeyCode::Enter => {
+                let cookies: Vec<Cookie> = page.cookies.into_iter().map(|entry| entry.cookie).collect();
+                return cookie_list_actions(store, &cookies).await;
+            }
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            _ => {}
+        }
     }
+}
 
-    println!(
-        "\n{} {}",
-        "Cookies in".bright_white(),
-        bucket.name.color(get_bucket_color(bucket.id)).bold()
-    );
-    println!("{}", "─".repeat(60).bright_black());
+/// Wait for a single key press, without echoing it
+fn read_key() -> Result<KeyCode> {
+    terminal::enable_raw_mode()?;
+    let key = loop {
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => break Ok(key.code),
+            Ok(_) => {}
+            Err(e) => break Err(e),
+        }
+    };
+    terminal::disable_raw_mode()?;
+    Ok(key?)
+}
+
+/// Number of weeks shown in the contribution heatmap
+const HEATMAP_WEEKS: i64 = 52;
+
+/// A horizontal bar scaled so that `max` fills `width` characters
+fn bar(count: i64, max: i64, width: usize) -> String {
+    if max <= 0 || count <= 0 {
+        return String::new();
+    }
+    let len = ((count as f64 / max as f64) * width as f64).round().max(1.0) as usize;
+    "█".repeat(len)
+}
 
-    for cookie in &cookies {
-        println!("\n   {}", cookie.content.bright_white());
+/// Print a labelled bar chart
+fn print_bar_chart(title: &str, rows: &[PeriodCount]) {
+    println!("\n{}", title.bright_white().bold());
+
+    let max = rows.iter().map(|r| r.count).max().unwrap_or(0);
+    let label_width = rows.iter().map(|r| r.label.chars().count()).max().unwrap_or(0);
+
+    for row in rows {
         println!(
-            "   {} {}",
-            "🕒".bright_black(),
-            cookie.formatted_created_at().bright_black()
+            "  {:>width$} {} {}",
+            row.label.bright_black(),
+            bar(row.count, max, 36).bright_cyan(),
+            row.count,
+            width = label_width
         );
     }
+}
 
-    println!("\n{}", "─".repeat(60).bright_black());
-    println!("Total: {} cookies", cookies.len().to_string().bright_cyan().bold());
+/// Print a GitHub-style grid of the last year, one column per week
+fn print_heatmap(stats: &JarStats) {
+    let per_day: std::collections::HashMap<&str, i64> =
+        stats.per_day.iter().map(|d| (d.label.as_str(), d.count)).collect();
 
-    wait_for_enter()?;
+    let today = chrono::Local::now().date_naive();
+    let this_monday = today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
+    let first_monday = this_monday - chrono::Duration::weeks(HEATMAP_WEEKS - 1);
 
-    Ok(())
+    println!("\n{}", format!("Last {} weeks", HEATMAP_WEEKS).bright_white().bold());
+
+    for (weekday, name) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+        let mut line = format!("  {:>3} ", name).bright_black().to_string();
+
+        for week in 0..HEATMAP_WEEKS {
+            let day = first_monday + chrono::Duration::days(week * 7 + weekday as i64);
+            if day > today {
+                line.push(' ');
+                continue;
+            }
+
+            let count = per_day
+                .get(day.format("%Y-%m-%d").to_string().as_str())
+                .copied()
+                .unwrap_or(0);
+            let cell = match count {
+                0 => "·".bright_black(),
+                1 => "■".green(),
+                2 => "■".bright_green(),
+                _ => "■".bright_green().bold(),
+            };
+            line.push_str(&cell.to_string());
+        }
+
+        println!("{}", line);
+    }
+}
+
+/// Print the stats dashboard
+pub fn print_stats(stats: &JarStats) {
+    println!("\n{}", "Stats:".bright_white().bold());
+    println!("{}", "─".repeat(60).bright_black());
+
+    println!(
+        "\n  {} cookies in {} buckets",
+        stats.total_cookies.to_string().bright_cyan().bold(),
+        stats.total_buckets.to_string().bright_cyan().bold()
+    );
+    println!(
+        "  {} Current streak: {} days   Longest streak: {} days",
+        "🔥".bright_white(),
+        stats.current_streak.to_string().bright_yellow().bold(),
+        stats.longest_streak.to_string().bright_yellow().bold()
+    );
+
+    print_heatmap(stats);
+    print_bar_chart("Cookies per week", &stats.per_week);
+    print_bar_chart("Cookies per month", &stats.per_month);
+    print_bar_chart("Cookies per bucket", &stats.per_bucket);
+    print_bar_chart("Most active weekdays", &stats.per_weekday);
+
+    println!("\n{}", "─".repeat(60).bright_black());
 }
 
 /// List all buckets with cookie counts
-async fn list_buckets(conn: &libsql::Connection) -> Result<()> {
-    let buckets = db::get_all_buckets(conn).await?;
+async fn list_buckets(store: &impl CookieStore) -> Result<()> {
+    let buckets = store.get_buckets_with_stats().await?;
 
     if buckets.is_empty() {
         println!("\n{}", "No buckets exist yet!".bright_yellow());
@@ -326,19 +852,29 @@ async fn list_buckets(conn: &libsql::Connection) -> Result<()> {
     println!("\n{}", "All Buckets:".bright_white().bold());
     println!("{}", "─".repeat(60).bright_black());
 
-    for bucket in &buckets {
-        let count = db::count_cookies_in_bucket(conn, bucket.id).await?;
+    for stats in &buckets {
+        let bucket = &stats.bucket;
         println!(
             "\n{} {} - {} cookies",
             "📁".bright_white(),
             bucket.name.color(get_bucket_color(bucket.id)).bold(),
-            count.to_string().bright_cyan()
+            stats.cookie_count.to_string().bright_cyan()
         );
         println!(
             "   {} Created {}",
             "🕒".bright_black(),
             bucket.formatted_created_at().bright_black()
         );
+        let span = match (stats.oldest_cookie_at, stats.newest_cookie_at) {
+            (Some(oldest), Some(newest)) => format!(
+                "Cookies from {} to {}, {}",
+                oldest.with_timezone(&Local).format("%b %d, %Y"),
+                newest.with_timezone(&Local).format("%b %d, %Y"),
+                stats.last_added()
+            ),
+            _ => "No cookies yet".to_string(),
+        };
+        println!("   {} {}", "📆".bright_black(), span.bright_black());
     }
 
     println!("\n{}", "─".repeat(60).bright_black());
@@ -348,3 +884,307 @@ async fn list_buckets(conn: &libsql::Connection) -> Result<()> {
 
     Ok(())
 }
+
+/// Offer to edit or delete one of the cookies that were just listed
+async fn cookie_list_actions(store: &impl CookieStore, cookies: &[Cookie]) -> Result<()> {
+    println!();
+
+    let options = vec![
+        CookieListOption::Back,
+        CookieListOption::EditCookie,
+        CookieListOption::DeleteCookie,
+    ];
+
+    let selection = Select::with_theme(&VimTheme)
+        .items(&options)
+        .default(0)
+        .interact()?;
+
+    match options[selection] {
+        CookieListOption::Back => {}
+        CookieListOption::EditCookie => {
+            if let Some(cookie) = select_cookie(cookies)? {
+                edit_cookie_flow(store, cookie).await?;
+            }
+        }
+        CookieListOption::DeleteCookie => {
+            if let Some(cookie) = select_cookie(cookies)? {
+                delete_cookie_flow(store, cookie).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Pick one cookie from a list, or None if the user cancels
+fn select_cookie(cookies: &[Cookie]) -> Result<Option<&Cookie>> {
+    println!("\n{}", "Select a cookie:".bright_white());
+    println!("{}", "(use j/k or arrow keys to navigate)".bright_black());
+
+    let mut items: Vec<String> = cookies
+        .iter()
+        .map(|c| {
+            format!(
+                "{}  {}",
+                preview(&c.content, 50),
+                c.formatted_created_at().bright_black()
+            )
+        })
+        .collect();
+
+    items.push("Cancel".bright_black().to_string());
+
+    let selection = Select::with_theme(&VimTheme)
+        .items(&items)
+        .default(0)
+        .interact()?;
+
+    Ok(cookies.get(selection))
+}
+
+/// Flow for editing a cookie's text or moving it to another bucket
+async fn edit_cookie_flow(store: &impl CookieStore, cookie: &Cookie) -> Result<()> {
+    let options = ["Edit text", "Move to another bucket"];
+
+    let selection = Select::with_theme(&VimTheme)
+        .items(&options)
+        .default(0)
+        .interact()?;
+
+    if selection == 0 {
+        let content = input_cookie("Cookie", &cookie.content)?;
+
+        store.update_cookie_content(cookie.id, &content).await?;
+        println!("\n{} Cookie updated!", "✓".bright_green());
+    } else {
+        let buckets: Vec<Bucket> = store.get_all_buckets()
+            .await?
+            .into_iter()
+            .filter(|b| b.id != cookie.bucket_id)
+            .collect();
+
+        if buckets.is_empty() {
+            println!("\n{}", "There are no other buckets to move it to.".bright_yellow());
+            wait_for_enter()?;
+            return Ok(());
+        }
+
+        println!("\n{}", "Move to which bucket?".bright_white());
+
+        let items: Vec<String> = buckets
+            .iter()
+            .map(|b| b.name.color(get_bucket_color(b.id)).bold().to_string())
+            .collect();
+
+        let selection = Select::with_theme(&VimTheme)
+            .items(&items)
+            .default(0)
+            .interact()?;
+
+        let bucket = &buckets[selection];
+        store.move_cookie_to_bucket(cookie.id, bucket.id).await?;
+        // Sync so the new bucket reference is visible to the replica straight away
+        sync_after_write(store).await;
+
+        println!(
+            "\n{} Cookie moved to \"{}\"",
+            "✓".bright_green(),
+            bucket.name.color(get_bucket_color(bucket.id)).bold()
+        );
+    }
+
+    Ok(())
+}
+
+/// Flow for deleting a cookie, after confirmation
+async fn delete_cookie_flow(store: &impl CookieStore, cookie: &Cookie) -> Result<()> {
+    let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Delete \"{}\"?", preview(&cookie.content, 50)))
+        .default(false)
+        .interact()?;
+
+    if confirmed {
+        store.delete_cookie(cookie.id).await?;
+        println!("\n{} Cookie deleted", "✓".bright_green());
+    }
+
+    Ok(())
+}
+
+/// A bucket as an item in a selection list, with its cookie count and latest activity
+fn bucket_item(stats: &BucketStats) -> String {
+    let bucket = &stats.bucket;
+    format!(
+        "{} ({} cookies, {})",
+        bucket.name.color(get_bucket_color(bucket.id)).bold(),
+        stats.cookie_count,
+        stats.last_added()
+    )
+}
+
+/// Pick a bucket from a list, showing cookie counts. Returns None if the user cancels.
+fn select_bucket(buckets: &[BucketStats]) -> Result<Option<&BucketStats>> {
+    let mut items: Vec<String> = buckets.iter().map(bucket_item).collect();
+
+    items.push("Cancel".bright_black().to_string());
+
+    let selection = Select::with_theme(&VimTheme)
+        .items(&items)
+        .default(0)
+        .interact()?;
+
+    Ok(buckets.get(selection))
+}
+
+/// Submenu for renaming, merging and deleting buckets
+async fn manage_buckets_flow(store: &impl CookieStore) -> Result<()> {
+    let buckets = store.get_buckets_with_stats().await?;
+
+    if buckets.is_empty() {
+        println!("\n{}", "No buckets exist yet!".bright_yellow());
+        wait_for_enter()?;
+        return Ok(());
+    }
+
+    println!("\n{}", "Which bucket would you like to manage?".bright_white());
+    println!("{}", "(use j/k or arrow keys to navigate)".bright_black());
+
+    let Some(selected) = select_bucket(&buckets)? else {
+        return Ok(());
+    };
+    let bucket = &selected.bucket;
+
+    let options = vec![
+        ManageBucketOption::Rename,
+        ManageBucketOption::Merge,
+        ManageBucketOption::Delete,
+        ManageBucketOption::Back,
+    ];
+
+    println!();
+    let selection = Select::with_theme(&VimTheme)
+        .items(&options)
+        .default(0)
+        .interact()?;
+
+    let others: Vec<BucketStats> = buckets.iter().filter(|b| b.bucket.id != bucket.id).cloned().collect();
+
+    match options[selection] {
+        ManageBucketOption::Rename => {
+            let new_name: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("New bucket name")
+                .with_initial_text(bucket.name.clone())
+                .interact_text()?;
+
+            match store.rename_bucket(bucket.id, &new_name).await {
+                Ok(()) => println!(
+                    "\n{} Renamed to \"{}\"",
+                    "✓".bright_green(),
+                    new_name.color(get_bucket_color(bucket.id)).bold()
+                ),
+                Err(e) if matches!(e.downcast_ref(), Some(CookieJarError::BucketExists(_))) => {
+                    println!(
+                        "\n{} There's already a bucket called \"{}\". Merge into it to combine the two.",
+                        "⚠".bright_yellow(),
+                        new_name
+                    );
+                    wait_for_enter()?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        ManageBucketOption::Merge => {
+            if others.is_empty() {
+                println!("\n{}", "There are no other buckets to merge into.".bright_yellow());
+                wait_for_enter()?;
+                return Ok(());
+            }
+
+            println!(
+                "\n{} {} {}",
+                "Merge".bright_white(),
+                bucket.name.color(get_bucket_color(bucket.id)).bold(),
+                "into which bucket?".bright_white()
+            );
+            let Some(target) = select_bucket(&others)? else {
+                return Ok(());
+            };
+            let target = &target.bucket;
+
+            let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!(
+                    "Move every cookie from \"{}\" into \"{}\" and remove \"{}\"?",
+                    bucket.name, target.name, bucket.name
+                ))
+                .default(false)
+                .interact()?;
+
+            if confirmed {
+                let moved = store.merge_buckets(bucket.id, target.id).await?;
+                println!(
+                    "\n{} Merged {} cookies into \"{}\"",
+                    "✓".bright_green(),
+                    moved.to_string().bright_cyan().bold(),
+                    target.name.color(get_bucket_color(target.id)).bold()
+                );
+            }
+        }
+        ManageBucketOption::Delete => delete_bucket_flow(store, bucket, &others).await?,
+        ManageBucketOption::Back => return Ok(()),
+    }
+
+    sync_after_write(store).await;
+
+    Ok(())
+}
+
+/// Flow for deleting a bucket, deciding what happens to the cookies inside it
+async fn delete_bucket_flow(store: &impl CookieStore, bucket: &Bucket, others: &[BucketStats]) -> Result<()> {
+    let count = store.count_cookies_in_bucket(bucket.id).await?;
+
+    let cookies = if count == 0 {
+        db::BucketCookies::Delete
+    } else {
+        let mut items = vec![format!("Delete its {} cookies too", count)];
+        if !others.is_empty() {
+            items.push("Move its cookies to another bucket".to_string());
+        }
+        items.push("Cancel".bright_black().to_string());
+
+        println!(
+            "\n{} {} {}",
+            bucket.name.color(get_bucket_color(bucket.id)).bold(),
+            "still has".bright_white(),
+            format!("{} cookies.", count).bright_white()
+        );
+        let selection = Select::with_theme(&VimTheme)
+            .items(&items)
+            .default(items.len() - 1)
+            .interact()?;
+
+        if selection == items.len() - 1 {
+            return Ok(());
+        } else if selection == 0 {
+            db::BucketCookies::Delete
+        } else {
+            println!("\n{}", "Move them to which bucket?".bright_white());
+            let Some(target) = select_bucket(others)? else {
+                return Ok(());
+            };
+            db::BucketCookies::MoveTo(target.bucket.id)
+        }
+    };
+
+    let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Delete bucket \"{}\"?", bucket.name))
+        .default(false)
+        .interact()?;
+
+    if confirmed {
+        store.delete_bucket(bucket.id, cookies).await?;
+        println!("\n{} Bucket deleted", "✓".bright_green());
+    }
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use crate::config::settings;
 use chrono::{DateTime, Local, Utc};
 
 /// Represents a bucket (category) for organizing cookies
@@ -11,13 +12,49 @@ pub struct Bucket {
 /// Represents a cookie (achievement/proud moment)
 #[derive(Debug, Clone)]
 pub struct Cookie {
-    #[allow(dead_code)]
     pub id: i64,
     pub bucket_id: i64,
     pub content: String,
     pub created_at: DateTime<Utc>,
 }
 
+/// A bucket along with a summary of the cookies in it
+#[derive(Debug, Clone)]
+pub struct BucketStats {
+    pub bucket: Bucket,
+    pub cookie_count: i64,
+    pub newest_cookie_at: Option<DateTime<Utc>>,
+    pub oldest_cookie_at: Option<DateTime<Utc>>,
+}
+
+/// A cookie along with the name of the bucket it is in
+#[derive(Debug, Clone)]
+pub struct CookieWithBucket {
+    pub cookie: Cookie,
+    pub bucket_name: String,
+}
+
+/// A tag attached to cookies via a #hashtag in their content
+#[derive(Debug, Clone)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+}
+
+/// Marks the start of a matched term in a search snippet
+pub const MATCH_START: &str = "\u{2}";
+/// Marks the end of a matched term in a search snippet
+pub const MATCH_END: &str = "\u{3}";
+
+/// A cookie found by full-text search, with a snippet of the matching text
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub cookie: Cookie,
+    pub bucket_name: String,
+    /// Excerpt of the content with matches wrapped in MATCH_START / MATCH_END
+    pub snippet: String,
+}
+
 impl Bucket {
     pub fn new(id: i64, name: String, created_at: i64) -> Self {
         Self {
@@ -34,6 +71,22 @@ impl Bucket {
     }
 }
 
+impl BucketStats {
+    /// When the newest cookie was added, e.g. "last added 3 days ago"
+    pub fn last_added(&self) -> String {
+        let Some(newest) = self.newest_cookie_at else {
+            return "no cookies yet".to_string();
+        };
+
+        let today = Local::now().date_naive();
+        match (today - newest.with_timezone(&Local).date_naive()).num_days() {
+            ..=0 => "last added today".to_string(),
+            1 => "last added yesterday".to_string(),
+            days => format!("last added {} days ago", days),
+        }
+    }
+}
+
 impl Cookie {
     pub fn new(id: i64, bucket_id: i64, content: String, created_at: i64) -> Self {
         Self {
@@ -47,6 +100,58 @@ impl Cookie {
     /// Format the creation timestamp in local time with a user-friendly format
     pub fn formatted_created_at(&self) -> String {
         let local_time = self.created_at.with_timezone(&Local);
-        local_time.format("%b %d, %Y at %I:%M %p").to_string()
+        local_time.format(&settings::current().date_format).to_string()
+    }
+}
+
+impl Tag {
+    /// Find the #hashtags in cookie content, lowercased and without duplicates.
+    /// A hashtag must start a word, and purely numeric ones ("#1") are ignored.
+    pub fn parse_hashtags(content: &str) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+
+        for word in content.split_whitespace() {
+            let Some(rest) = word.strip_prefix('#') else {
+                continue;
+            };
+
+            let name: String = rest
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '-')
+                .collect::<String>()
+                .trim_end_matches('-')
+                .to_lowercase();
+
+            if !name.is_empty() && !name.chars().all(|c| c.is_ascii_digit()) && !tags.contains(&name) {
+                tags.push(name);
+            }
+        }
+
+        tags
+    }
+}
+
+impl SearchResult {
+    /// Split the snippet into `(text, is_match)` segments for highlighting
+    pub fn snippet_parts(&self) -> Vec<(&str, bool)> {
+        let mut parts = Vec::new();
+        let mut rest = self.snippet.as_str();
+
+        while let Some(start) = rest.find(MATCH_START) {
+            if start > 0 {
+                parts.push((&rest[..start], false));
+            }
+            rest = &rest[start + MATCH_START.len()..];
+
+            let end = rest.find(MATCH_END).unwrap_or(rest.len());
+            parts.push((&rest[..end], true));
+            rest = rest.get(end + MATCH_END.len()..).unwrap_or("");
+        }
+
+        if !rest.is_empty() {
+            parts.push((rest, false));
+        }
+
+        parts
     }
 }
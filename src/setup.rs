@@ -0,0 +1,181 @@
+use crate::cli;
+use crate::config::{self, settings};
+use crate::db;
+use crate::menu::{get_bucket_color, sync_after_write, VimTheme};
+use crate::store::CookieStore;
+use anyhow::Result;
+use colored::*;
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, Password, Select};
+use std::io::IsTerminal;
+
+/// Bucket suggested when the wizard asks for a first one
+const STARTER_BUCKET: &str = "Wins";
+
+/// Whether nothing has been set up yet: no jar, no .env and no config.toml.
+/// Only interactive sessions count, so scripts never end up in the wizard.
+pub fn is_first_run() -> Result<bool> {
+    let interactive = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+    Ok(interactive
+        && std::env::var("TURSO_DATABASE_URL").is_err()
+        && !config::get_db_path()?.exists()
+        && !config::get_env_path()?.exists()
+        && !config::get_config_path()?.exists())
+}
+
+/// Walk through choosing where the jar lives, writing ~/.cookie_jar/.env and config.toml.
+/// Runs before settings are loaded, so the jar opens with whatever was chosen here.
+/// Returns false if the user backed out.
+pub async fn run(first_run: bool) -> Result<bool> {
+    let dir = config::ensure_cookiejar_dir()?;
+
+    println!("\n{}", "🍪 Welcome to Cookie Jar".bright_white().bold());
+    println!("{}", "─".repeat(60).bright_black());
+    println!("A cookie jar is where you keep your wins: things you got through,");
+    println!("finished or were proud of. When things get hard, reach in and pull");
+    println!("one out as a reminder of what you've already done.");
+    println!();
+    println!("Cookies live in {}, like \"Work\", \"Fitness\" or \"Kindness\".", "buckets".bright_cyan());
+    println!("{}", "─".repeat(60).bright_black());
+
+    let existing = settings::Settings::load().ok();
+    let previous_url = existing.as_ref().and_then(|settings| settings.turso_url.clone());
+    if !first_run && (previous_url.is_some() || config::get_config_path()?.exists()) {
+        let again = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("This jar is already set up. Go through setup again?")
+            .default(false)
+            .interact()?;
+        if !again {
+            return Ok(false);
+        }
+    }
+
+    println!("\n{}", "Where should the jar live?".bright_white());
+    let options = [
+        "On this machine only",
+        "Synced with Turso, so it follows you between machines",
+    ];
+    let choice = Select::with_theme(&VimTheme).items(&options).default(0).interact()?;
+
+    println!("{} {}", "✔".bright_green(), options[choice]);
+
+    let credentials = if choice == 1 { ask_for_credentials().await? } else { None };
+
+    match &credentials {
+        Some((url, token)) => {
+            // A local-only jar with cookies in it is copied across rather than replaced
+            if previous_url.is_none() && config::get_db_path()?.exists() {
+                cli::attach_jar(url, token).await?;
+            } else {
+                config::update_env_file(&[
+                    ("TURSO_DATABASE_URL", Some(url)),
+                    ("TURSO_AUTH_TOKEN", Some(token)),
+                    ("COOKIE_JAR_STORAGE", None),
+                ])?;
+            }
+            settings::set("storage", "auto")?;
+        }
+        None => {
+            settings::set("storage", "local")?;
+        }
+    }
+
+    let default_bucket = existing
+        .and_then(|settings| settings.default_bucket)
+        .unwrap_or_else(|| STARTER_BUCKET.to_string());
+    let bucket: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Name your first bucket (cj add puts cookies here by default)")
+        .default(default_bucket)
+        .interact_text()?;
+    settings::set("default_bucket", &bucket)?;
+
+    // Pick up what was just written before the jar is opened
+    dotenvy::from_path_override(config::get_env_path()?).ok();
+
+    println!(
+        "\n{} Saved your settings in {}",
+        "✓".bright_green(),
+        dir.display().to_string().bright_black()
+    );
+
+    Ok(true)
+}
+
+/// Ask for a Turso URL and token until they work, or the user settles for local-only
+async fn ask_for_credentials() -> Result<Option<(String, String)>> {
+    println!(
+        "{}",
+        "Create a database with `turso db create cookie-jar`, then get its URL with\n\
+         `turso db show cookie-jar --url` and a token with `turso db tokens create cookie-jar`."
+            .bright_black()
+    );
+
+    loop {
+        let url: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Turso database URL")
+            .validate_with(|input: &String| settings::check("turso_url", input.trim()).map_err(|e| e.to_string()))
+            .interact_text()?;
+        let token = Password::with_theme(&ColorfulTheme::default())
+            .with_prompt("Turso auth token")
+            .interact()?;
+        let (url, token) = (url.trim().to_string(), token.trim().to_string());
+
+        println!("{}", "Checking the connection...".bright_black());
+        match db::check_remote(&url, &token).await {
+            Ok(()) => {
+                println!("{} Connected to Turso", "✓".bright_green());
+                return Ok(Some((url, token)));
+            }
+            Err(e) => {
+                println!("{} {:#}", "✗".bright_red(), e);
+                let retry = Select::with_theme(&VimTheme)
+                    .items(&["Try again", "Keep the jar on this machine for now"])
+                    .default(0)
+                    .interact()?;
+                if retry == 1 {
+                    println!(
+                        "{}",
+                        "You can connect it later with `cj attach --url <url>`.".bright_black()
+                    );
+                    return Ok(None);
+                }
+            }
+        }
+    }
+}
+
+/// Create the default bucket chosen during setup and show where to go next
+pub async fn finish(store: &impl CookieStore) -> Result<()> {
+    if let Some(name) = &settings::current().default_bucket {
+        let bucket = match store.get_bucket_by_name(name).await? {
+            Some(bucket) => bucket,
+            None => {
+                let bucket = store.create_bucket(name).await?;
+                sync_after_write(store).await;
+                bucket
+            }
+        };
+        println!(
+            "{} Your first bucket is \"{}\"",
+            "✓".bright_green(),
+            bucket.name.color(get_bucket_color(bucket.id)).bold()
+        );
+    }
+
+    let storage = match store.mode() {
+        db::StorageMode::Local => "on this machine",
+        db::StorageMode::Remote => "synced with Turso",
+        db::StorageMode::Offline => "synced with Turso, but read-only until Turso is reachable again",
+    };
+    println!("{} Your jar is ready, {}.", "✨".bright_green(), storage);
+    println!();
+    for (command, what) in [
+        ("cj add \"Ran my first 10k\"", "add a cookie"),
+        ("cj dip", "pull a random one out"),
+        ("cj", "open the menu"),
+        ("cj config list", "change settings later"),
+    ] {
+        println!("   {:<28} {}", command.bright_cyan(), what.bright_black());
+    }
+
+    Ok(())
+}
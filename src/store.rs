@@ -0,0 +1,222 @@
+// Nothing in cj itself keeps a jar in memory; it's there for tests and experiments
+pub mod memory;
+
+use crate::db::migrations::{self, AppliedMigration, Migration};
+use crate::db::{
+    self, stats::JarStats, BucketCookies, CookiePage, CookieQuery, Database, DipMode, PageCursor, StorageMode,
+    SyncStatus,
+};
+use crate::import::{self, ImportPlan};
+use crate::models::{Bucket, BucketStats, Cookie, CookieWithBucket, SearchResult, Tag};
+use anyhow::Result;
+
+/// Everything the menu and commands need from a jar, independent of where it is kept.
+/// `LibsqlStore` is the real thing; `MemoryStore` keeps a jar in memory.
+#[allow(async_fn_in_trait)]
+pub trait CookieStore {
+    // ============ BUCKETS ============
+
+    async fn create_bucket(&self, name: &str) -> Result<Bucket>;
+    /// All buckets, by name
+    async fn get_all_buckets(&self) -> Result<Vec<Bucket>>;
+    async fn get_bucket_by_name(&self, name: &str) -> Result<Option<Bucket>>;
+    /// All buckets with their cookie counts and activity, by name
+    async fn get_buckets_with_stats(&self) -> Result<Vec<BucketStats>>;
+    async fn count_cookies_in_bucket(&self, bucket_id: i64) -> Result<i64>;
+    async fn rename_bucket(&self, id: i64, new_name: &str) -> Result<()>;
+    /// Move every cookie into another bucket and remove the emptied one.
+    /// Returns the number of cookies moved.
+    async fn merge_buckets(&self, from_id: i64, into_id: i64) -> Result<u64>;
+    async fn delete_bucket(&self, id: i64, cookies: BucketCookies) -> Result<()>;
+
+    // ============ COOKIES ============
+
+    /// Add a cookie, returning its ID
+    async fn create_cookie(&self, bucket_id: i64, content: &str) -> Result<i64>;
+    async fn get_cookie(&self, id: i64) -> Result<Option<Cookie>>;
+    async fn query_cookies(&self, query: &CookieQuery) -> Result<Vec<Cookie>>;
+    async fn query_cookies_with_buckets(&self, query: &CookieQuery) -> Result<Vec<CookieWithBucket>>;
+    /// One page of the cookies matching a query, starting after `after`
+    async fn get_cookie_page(&self, query: &CookieQuery, after: Option<PageCursor>, page_size: i64) -> Result<CookiePage>;
+    async fn update_cookie_content(&self, id: i64, content: &str) -> Result<()>;
+    async fn move_cookie_to_bucket(&self, id: i64, bucket_id: i64) -> Result<()>;
+    /// Change a cookie's text, its bucket or both, all or nothing
+    async fn edit_cookie(&self, id: i64, content: Option<&str>, bucket_id: Option<i64>) -> Result<()>;
+    async fn delete_cookie(&self, id: i64) -> Result<()>;
+    /// Pull out a random cookie, optionally from one bucket
+    async fn reach_into_jar(&self, bucket_id: Option<i64>, mode: DipMode) -> Result<Option<Cookie>>;
+    async fn record_cookie_view(&self, cookie_id: i64) -> Result<()>;
+
+    // ============ TAGS, SEARCH AND STATS ============
+
+    /// Every tag in use with its number of cookies, most used first
+    async fn get_tag_counts(&self) -> Result<Vec<(Tag, i64)>>;
+    /// Cookies matching every word of `query`, best first
+    async fn search_cookies(&self, query: &str, tag: Option<&str>) -> Result<Vec<SearchResult>>;
+    async fn stats(&self) -> Result<JarStats>;
+
+    // ============ IMPORT AND SCHEMA ============
+
+    /// Write a planned import all at once, creating its new buckets.
+    /// Returns the number of cookies imported.
+    async fn import(&self, plan: &ImportPlan) -> Result<usize>;
+    /// The schema version the jar is at
+    async fn schema_version(&self) -> Result<i64>;
+    /// Every migration applied to the jar, oldest first
+    async fn applied_migrations(&self) -> Result<Vec<AppliedMigration>>;
+    /// Migrations the jar still needs
+    async fn pending_migrations(&self) -> Result<Vec<&'static Migration>>;
+    /// Apply every pending migration, returning the ones applied
+    async fn migrate(&self) -> Result<Vec<&'static Migration>>;
+
+    // ============ SYNC ============
+
+    /// Bring the store in line with its remote, if it has one
+    async fn sync(&self) -> Result<()>;
+    fn mode(&self) -> StorageMode;
+    fn sync_status(&self) -> SyncStatus;
+}
+
+/// A jar in a libsql database: a local file, or a replica synced with Turso
+pub struct LibsqlStore {
+    database: Database,
+    conn: libsql::Connection,
+}
+
+impl LibsqlStore {
+    pub fn new(database: Database) -> Result<Self> {
+        let conn = database.connect()?;
+        Ok(Self { database, conn })
+    }
+
+    /// The connection, for work the trait doesn't cover such as setting up the schema
+    pub fn conn(&self) -> &libsql::Connection {
+        &self.conn
+    }
+
+    pub fn database(&self) -> &Database {
+        &self.database
+    }
+}
+
+impl CookieStore for LibsqlStore {
+    async fn create_bucket(&self, name: &str) -> Result<Bucket> {
+        db::create_bucket(&self.conn, name).await
+    }
+
+    async fn get_all_buckets(&self) -> Result<Vec<Bucket>> {
+        db::get_all_buckets(&self.conn).await
+    }
+
+    async fn get_bucket_by_name(&self, name: &str) -> Result<Option<Bucket>> {
+        db::get_bucket_by_name(&self.conn, name).await
+    }
+
+    async fn get_buckets_with_stats(&self) -> Result<Vec<BucketStats>> {
+        db::get_buckets_with_stats(&self.conn).await
+    }
+
+    async fn count_cookies_in_bucket(&self, bucket_id: i64) -> Result<i64> {
+        db::count_cookies_in_bucket(&self.conn, bucket_id).await
+    }
+
+    async fn rename_bucket(&self, id: i64, new_name: &str) -> Result<()> {
+        db::rename_bucket(&self.conn, id, new_name).await
+    }
+
+    async fn merge_buckets(&self, from_id: i64, into_id: i64) -> Result<u64> {
+        db::merge_buckets(&self.conn, from_id, into_id).await
+    }
+
+    async fn delete_bucket(&self, id: i64, cookies: BucketCookies) -> Result<()> {
+        db::delete_bucket(&self.conn, id, cookies).await
+    }
+
+    async fn create_cookie(&self, bucket_id: i64, content: &str) -> Result<i64> {
+        db::create_cookie(&self.conn, bucket_id, content).await
+    }
+
+    async fn get_cookie(&self, id: i64) -> Result<Option<Cookie>> {
+        db::get_cookie(&self.conn, id).await
+    }
+
+    async fn query_cookies(&self, query: &CookieQuery) -> Result<Vec<Cookie>> {
+        db::query_cookies(&self.conn, query).await
+    }
+
+    async fn query_cookies_with_buckets(&self, query: &CookieQuery) -> Result<Vec<CookieWithBucket>> {
+        db::query_cookies_with_buckets(&self.conn, query).await
+    }
+
+    async fn get_cookie_page(&self, query: &CookieQuery, after: Option<PageCursor>, page_size: i64) -> Result<CookiePage> {
+        db::get_cookie_page(&self.conn, query, after, page_size).await
+    }
+
+    async fn update_cookie_content(&self, id: i64, content: &str) -> Result<()> {
+        db::update_cookie_content(&self.conn, id, content).await
+    }
+
+    async fn move_cookie_to_bucket(&self, id: i64, bucket_id: i64) -> Result<()> {
+        db::move_cookie_to_bucket(&self.conn, id, bucket_id).await
+    }
+
+    async fn edit_cookie(&self, id: i64, content: Option<&str>, bucket_id: Option<i64>) -> Result<()> {
+        db::edit_cookie(&self.conn, id, content, bucket_id).await
+    }
+
+    async fn delete_cookie(&self, id: i64) -> Result<()> {
+        db::delete_cookie(&self.conn, id).await
+    }
+
+    async fn reach_into_jar(&self, bucket_id: Option<i64>, mode: DipMode) -> Result<Option<Cookie>> {
+        db::reach_into_jar(&self.conn, bucket_id, mode).await
+    }
+
+    async fn record_cookie_view(&self, cookie_id: i64) -> Result<()> {
+        db::record_cookie_view(&self.conn, cookie_id).await
+    }
+
+    async fn get_tag_counts(&self) -> Result<Vec<(Tag, i64)>> {
+        db::get_tag_counts(&self.conn).await
+    }
+
+    async fn search_cookies(&self, query: &str, tag: Option<&str>) -> Result<Vec<SearchResult>> {
+        db::search_cookies(&self.conn, query, tag).await
+    }
+
+    async fn stats(&self) -> Result<JarStats> {
+        db::stats::compute(&self.conn).await
+    }
+
+    async fn import(&self, plan: &ImportPlan) -> Result<usize> {
+        import::apply(&self.conn, plan).await
+    }
+
+    async fn schema_version(&self) -> Result<i64> {
+        migrations::current_version(&self.conn).await
+    }
+
+    async fn applied_migrations(&self) -> Result<Vec<AppliedMigration>> {
+        migrations::applied_migrations(&self.conn).await
+    }
+
+    async fn pending_migrations(&self) -> Result<Vec<&'static Migration>> {
+        migrations::pending(&self.conn).await
+    }
+
+    async fn migrate(&self) -> Result<Vec<&'static Migration>> {
+        migrations::migrate(&self.conn).await
+    }
+
+    async fn sync(&self) -> Result<()> {
+        self.database.sync().await.map(drop)
+    }
+
+    fn mode(&self) -> StorageMode {
+        self.database.mode()
+    }
+
+    fn sync_status(&self) -> SyncStatus {
+        self.database.sync_status()
+    }
+}
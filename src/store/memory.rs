@@ -0,0 +1,425 @@
+use super::CookieStore;
+use crate::db::{
+    self, stats::JarStats, BucketCookies, CookiePage, CookieQuery, DipMode, PageCursor, SortOrder, StorageMode,
+    SyncStatus,
+};
+use crate::db::migrations::{self, AppliedMigration, Migration};
+use crate::error::{BucketRef, CookieJarError};
+use crate::import::ImportPlan;
+use crate::models::{Bucket, BucketStats, Cookie, CookieWithBucket, SearchResult, Tag, MATCH_END, MATCH_START};
+use anyhow::Result;
+use rand::seq::SliceRandom;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A jar kept entirely in memory, for trying out flows without touching a database
+#[derive(Default)]
+pub struct MemoryStore {
+    jar: Mutex<Jar>,
+}
+
+#[derive(Default)]
+struct Jar {
+    buckets: Vec<Bucket>,
+    cookies: Vec<Cookie>,
+    /// When each cookie was last shown, as a Unix timestamp
+    views: HashMap<i64, i64>,
+    /// Every tag ever used, numbered in the order they were first seen
+    tags: Vec<Tag>,
+    // Buckets and cookies number their IDs separately, like the tables' AUTOINCREMENT
+    last_bucket_id: i64,
+    last_cookie_id: i64,
+}
+
+impl Jar {
+    fn add_bucket(&mut self, name: &str) -> Result<Bucket> {
+        self.check_unique(name)?;
+        self.last_bucket_id += 1;
+        let bucket = Bucket::new(self.last_bucket_id, name.to_string(), chrono::Utc::now().timestamp());
+        self.buckets.push(bucket.clone());
+        Ok(bucket)
+    }
+
+    fn add_cookie(&mut self, bucket_id: i64, content: &str, timestamp: i64) -> i64 {
+        self.last_cookie_id += 1;
+        self.cookies.push(Cookie::new(self.last_cookie_id, bucket_id, content.to_string(), timestamp));
+        self.register_tags(content);
+        self.last_cookie_id
+    }
+
+    /// Give any new #hashtags in `content` the next tag ID
+    fn register_tags(&mut self, content: &str) {
+        for name in Tag::parse_hashtags(content) {
+            if !self.tags.iter().any(|tag| tag.name == name) {
+                let id = self.tags.len() as i64 + 1;
+                self.tags.push(Tag { id, name });
+            }
+        }
+    }
+
+    fn tag_id(&self, name: &str) -> i64 {
+        self.tags.iter().find(|tag| tag.name == name).map_or(0, |tag| tag.id)
+    }
+
+    fn bucket(&self, id: i64) -> Result<&Bucket> {
+        match self.buckets.iter().find(|bucket| bucket.id == id) {
+            Some(bucket) => Ok(bucket),
+            None => Err(CookieJarError::BucketNotFound(BucketRef::Id(id)).into()),
+        }
+    }
+
+    fn cookie_mut(&mut self, id: i64) -> Result<&mut Cookie> {
+        match self.cookies.iter_mut().find(|cookie| cookie.id == id) {
+            Some(cookie) => Ok(cookie),
+            None => anyhow::bail!("Cookie #{} does not exist", id),
+        }
+    }
+
+    fn check_unique(&self, name: &str) -> Result<()> {
+        if self.buckets.iter().any(|bucket| bucket.name == name) {
+            return Err(CookieJarError::BucketExists(name.to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// Cookies matching a query's filters, in its order, after an optional cursor
+    fn select(&self, query: &CookieQuery, after: Option<PageCursor>) -> Vec<CookieWithBucket> {
+        let mut cookies: Vec<&Cookie> = self
+            .cookies
+            .iter()
+            .filter(|cookie| query.bucket_id.is_none_or(|id| cookie.bucket_id == id))
+            .filter(|cookie| query.tag.as_ref().is_none_or(|tag| Tag::parse_hashtags(&cookie.content).contains(tag)))
+            .filter(|cookie| query.in_range(cookie))
+            .filter(|cookie| after.is_none_or(|cursor| cursor.precedes(cookie, query.order)))
+            .collect();
+
+        cookies.sort_by_key(|cookie| (cookie.created_at, cookie.id));
+        if query.order == SortOrder::NewestFirst {
+            cookies.reverse();
+        }
+
+        cookies
+            .into_iter()
+            .skip(query.offset.unwrap_or(0).max(0) as usize)
+            .take(query.limit.map_or(usize::MAX, |limit| limit.max(0) as usize))
+            .map(|cookie| CookieWithBucket {
+                cookie: cookie.clone(),
+                bucket_name: self.bucket(cookie.bucket_id).map(|b| b.name.clone()).unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn jar(&self) -> std::sync::MutexGuard<'_, Jar> {
+        self.jar.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Wrap the words of `content` that start with one of `terms` in match markers
+fn highlight(content: &str, terms: &[String]) -> String {
+    content
+        .split(' ')
+        .map(|word| {
+            let lower = word.to_lowercase();
+            if terms.iter().any(|term| lower.trim_start_matches(|c: char| !c.is_alphanumeric()).starts_with(term.as_str())) {
+                format!("{}{}{}", MATCH_START, word, MATCH_END)
+            } else {
+                word.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl CookieStore for MemoryStore {
+    async fn create_bucket(&self, name: &str) -> Result<Bucket> {
+        self.jar().add_bucket(name)
+    }
+
+    async fn get_all_buckets(&self) -> Result<Vec<Bucket>> {
+        let mut buckets = self.jar().buckets.clone();
+        buckets.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(buckets)
+    }
+
+    async fn get_bucket_by_name(&self, name: &str) -> Result<Option<Bucket>> {
+        Ok(self.jar().buckets.iter().find(|bucket| bucket.name == name).cloned())
+    }
+
+    async fn get_buckets_with_stats(&self) -> Result<Vec<BucketStats>> {
+        let jar = self.jar();
+        let mut stats: Vec<BucketStats> = jar
+            .buckets
+            .iter()
+            .map(|bucket| {
+                let times: Vec<_> = jar
+                    .cookies
+                    .iter()
+                    .filter(|cookie| cookie.bucket_id == bucket.id)
+                    .map(|cookie| cookie.created_at)
+                    .collect();
+                BucketStats {
+                    bucket: bucket.clone(),
+                    cookie_count: times.len() as i64,
+                    newest_cookie_at: times.iter().max().copied(),
+                    oldest_cookie_at: times.iter().min().copied(),
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| a.bucket.name.cmp(&b.bucket.name));
+        Ok(stats)
+    }
+
+    async fn count_cookies_in_bucket(&self, bucket_id: i64) -> Result<i64> {
+        Ok(self.jar().cookies.iter().filter(|cookie| cookie.bucket_id == bucket_id).count() as i64)
+    }
+
+    async fn rename_bucket(&self, id: i64, new_name: &str) -> Result<()> {
+        let mut jar = self.jar();
+        jar.bucket(id)?;
+        jar.check_unique(new_name)?;
+        for bucket in jar.buckets.iter_mut().filter(|bucket| bucket.id == id) {
+            bucket.name = new_name.to_string();
+        }
+        Ok(())
+    }
+
+    async fn merge_buckets(&self, from_id: i64, into_id: i64) -> Result<u64> {
+        if from_id == into_id {
+            anyhow::bail!("Cannot merge a bucket into itself");
+        }
+        let mut jar = self.jar();
+        jar.bucket(from_id)?;
+        jar.bucket(into_id)?;
+        let mut moved = 0;
+        for cookie in jar.cookies.iter_mut().filter(|cookie| cookie.bucket_id == from_id) {
+            cookie.bucket_id = into_id;
+            moved += 1;
+        }
+        jar.buckets.retain(|bucket| bucket.id != from_id);
+        Ok(moved)
+    }
+
+    async fn delete_bucket(&self, id: i64, cookies: BucketCookies) -> Result<()> {
+        let mut jar = self.jar();
+        if let BucketCookies::MoveTo(target_id) = cookies {
+            if target_id == id {
+                anyhow::bail!("Cannot move cookies into the bucket being deleted");
+            }
+            jar.bucket(target_id)?;
+        }
+        jar.bucket(id)?;
+        match cookies {
+            BucketCookies::Delete => jar.cookies.retain(|cookie| cookie.bucket_id != id),
+            BucketCookies::MoveTo(target_id) => {
+                for cookie in jar.cookies.iter_mut().filter(|cookie| cookie.bucket_id == id) {
+                    cookie.bucket_id = target_id;
+                }
+            }
+        }
+        jar.buckets.retain(|bucket| bucket.id != id);
+        Ok(())
+    }
+
+    async fn create_cookie(&self, bucket_id: i64, content: &str) -> Result<i64> {
+        db::validate_cookie_content(content)?;
+        let mut jar = self.jar();
+        jar.bucket(bucket_id)?;
+        Ok(jar.add_cookie(bucket_id, content, chrono::Utc::now().timestamp()))
+    }
+
+    async fn get_cookie(&self, id: i64) -> Result<Option<Cookie>> {
+        Ok(self.jar().cookies.iter().find(|cookie| cookie.id == id).cloned())
+    }
+
+    async fn query_cookies(&self, query: &CookieQuery) -> Result<Vec<Cookie>> {
+        Ok(self.jar().select(query, None).into_iter().map(|c| c.cookie).collect())
+    }
+
+    async fn query_cookies_with_buckets(&self, query: &CookieQuery) -> Result<Vec<CookieWithBucket>> {
+        Ok(self.jar().select(query, None))
+    }
+
+    async fn get_cookie_page(&self, query: &CookieQuery, after: Option<PageCursor>, page_size: i64) -> Result<CookiePage> {
+        let query = query.clone().limit(Some(page_size + 1)).offset(None);
+        Ok(db::into_page(self.jar().select(&query, after), page_size))
+    }
+
+    async fn update_cookie_content(&self, id: i64, content: &str) -> Result<()> {
+        db::validate_cookie_content(content)?;
+        let mut jar = self.jar();
+        jar.cookie_mut(id)?.content = content.to_string();
+        jar.register_tags(content);
+        Ok(())
+    }
+
+    async fn move_cookie_to_bucket(&self, id: i64, bucket_id: i64) -> Result<()> {
+        let mut jar = self.jar();
+        jar.cookie_mut(id)?;
+        jar.bucket(bucket_id)?;
+        jar.cookie_mut(id)?.bucket_id = bucket_id;
+        Ok(())
+    }
+
+    async fn edit_cookie(&self, id: i64, content: Option<&str>, bucket_id: Option<i64>) -> Result<()> {
+        if let Some(content) = content {
+            db::validate_cookie_content(content)?;
+        }
+        let mut jar = self.jar();
+        jar.cookie_mut(id)?;
+        if let Some(bucket_id) = bucket_id {
+            jar.bucket(bucket_id)?;
+            jar.cookie_mut(id)?.bucket_id = bucket_id;
+        }
+        if let Some(content) = content {
+            jar.cookie_mut(id)?.content = content.to_string();
+            jar.register_tags(content);
+        }
+        Ok(())
+    }
+
+    async fn delete_cookie(&self, id: i64) -> Result<()> {
+        let mut jar = self.jar();
+        jar.cookie_mut(id)?;
+        jar.cookies.retain(|cookie| cookie.id != id);
+        jar.views.remove(&id);
+        Ok(())
+    }
+
+    async fn reach_into_jar(&self, bucket_id: Option<i64>, mode: DipMode) -> Result<Option<Cookie>> {
+        let jar = self.jar();
+        let candidates: Vec<&Cookie> = jar
+            .cookies
+            .iter()
+            .filter(|cookie| bucket_id.is_none_or(|id| cookie.bucket_id == id))
+            .collect();
+
+        Ok(match mode {
+            DipMode::Uniform => candidates.choose(&mut rand::thread_rng()).map(|cookie| (*cookie).clone()),
+            DipMode::FavourUnseen => db::pick_unseen(
+                candidates
+                    .into_iter()
+                    .map(|cookie| (cookie.clone(), jar.views.get(&cookie.id).copied()))
+                    .collect(),
+            ),
+        })
+    }
+
+    async fn record_cookie_view(&self, cookie_id: i64) -> Result<()> {
+        let mut jar = self.jar();
+        jar.cookie_mut(cookie_id)?;
+        jar.views.insert(cookie_id, chrono::Utc::now().timestamp());
+        Ok(())
+    }
+
+    async fn get_tag_counts(&self) -> Result<Vec<(Tag, i64)>> {
+        let jar = self.jar();
+        let mut counts: HashMap<String, i64> = HashMap::new();
+        for cookie in &jar.cookies {
+            for tag in Tag::parse_hashtags(&cookie.content) {
+                *counts.entry(tag).or_insert(0) += 1;
+            }
+        }
+
+        let mut tags: Vec<(Tag, i64)> = counts
+            .into_iter()
+            .map(|(name, count)| (Tag { id: jar.tag_id(&name), name }, count))
+            .collect();
+        tags.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.name.cmp(&b.name)));
+        Ok(tags)
+    }
+
+    async fn search_cookies(&self, query: &str, tag: Option<&str>) -> Result<Vec<SearchResult>> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let tag = tag.map(db::normalize_tag);
+
+        // A plain substring match on every term, newest first. The real index ranks
+        // results and stems words, which this doesn't try to copy.
+        let jar = self.jar();
+        Ok(jar
+            .cookies
+            .iter()
+            .rev()
+            .filter(|cookie| {
+                let content = cookie.content.to_lowercase();
+                terms.iter().all(|term| content.contains(term.as_str()))
+            })
+            .filter(|cookie| tag.as_ref().is_none_or(|tag| Tag::parse_hashtags(&cookie.content).contains(tag)))
+            .map(|cookie| SearchResult {
+                cookie: cookie.clone(),
+                bucket_name: jar.bucket(cookie.bucket_id).map(|b| b.name.clone()).unwrap_or_default(),
+                snippet: highlight(&cookie.content, &terms),
+            })
+            .collect())
+    }
+
+    async fn stats(&self) -> Result<JarStats> {
+        let jar = self.jar();
+        Ok(db::stats::from_cookies(&jar.cookies, &jar.buckets))
+    }
+
+    async fn import(&self, plan: &ImportPlan) -> Result<usize> {
+        let mut jar = self.jar();
+        // Check everything first so a bad plan leaves the jar untouched, as a rolled back transaction would
+        for name in &plan.new_buckets {
+            jar.check_unique(name)?;
+        }
+        for entry in &plan.to_import {
+            let bucket = &entry.record.bucket;
+            if !plan.new_buckets.contains(bucket) && !jar.buckets.iter().any(|b| &b.name == bucket) {
+                return Err(CookieJarError::BucketNotFound(BucketRef::Name(bucket.clone())).into());
+            }
+        }
+
+        for name in &plan.new_buckets {
+            jar.add_bucket(name)?;
+        }
+        let bucket_ids: HashMap<String, i64> = jar.buckets.iter().map(|b| (b.name.clone(), b.id)).collect();
+
+        let now = chrono::Utc::now().timestamp();
+        for entry in &plan.to_import {
+            let record = &entry.record;
+            let timestamp = record.created_at.map(|t| t.timestamp()).unwrap_or(now);
+            jar.add_cookie(bucket_ids[&record.bucket], &record.content, timestamp);
+        }
+        Ok(plan.to_import.len())
+    }
+
+    // A jar in memory starts out at the latest schema, without a record of getting there
+
+    async fn schema_version(&self) -> Result<i64> {
+        Ok(migrations::latest_version())
+    }
+
+    async fn applied_migrations(&self) -> Result<Vec<AppliedMigration>> {
+        Ok(Vec::new())
+    }
+
+    async fn pending_migrations(&self) -> Result<Vec<&'static Migration>> {
+        Ok(Vec::new())
+    }
+
+    async fn migrate(&self) -> Result<Vec<&'static Migration>> {
+        Ok(Vec::new())
+    }
+
+    async fn sync(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn mode(&self) -> StorageMode {
+        StorageMode::Local
+    }
+
+    fn sync_status(&self) -> SyncStatus {
+        SyncStatus::default()
+    }
+}
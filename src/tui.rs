@@ -0,0 +1,794 @@
+use crate::config::settings::{self, Theme};
+use crate::dates;
+use crate::db;
+use crate::menu::get_bucket_color;
+use crate::store::CookieStore;
+use crate::models::{Bucket, Cookie, CookieWithBucket, Tag};
+use anyhow::Result;
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
+    terminal::{self, Clear, ClearType},
+};
+use std::io::{stdout, Stdout, Write};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Height of the detail pane, including its border
+const DETAIL_HEIGHT: u16 = 8;
+/// Number of cookies loaded at a time; n/p turn the page
+const PAGE_SIZE: i64 = 100;
+/// Smallest terminal the browser will draw into
+const MIN_COLS: u16 = 50;
+const MIN_ROWS: u16 = 16;
+
+/// Which pane has keyboard focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Buckets,
+    Cookies,
+}
+
+/// What a line of typed input will be used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputKind {
+    Search,
+    Filter,
+    AddCookie,
+    EditCookie(i64),
+}
+
+/// What keys currently do
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Mode {
+    Normal,
+    Input { kind: InputKind, buffer: String },
+    ConfirmDelete(i64),
+    /// Picking a destination bucket (an index into `buckets`) for a cookie
+    MoveCookie { id: i64, target: usize },
+}
+
+/// Restores the terminal when the browser exits, even on error
+struct RawModeGuard;
+
+impl RawModeGuard {
+    fn enter() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(stdout(), Hide, Clear(ClearType::All))?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), Show, ResetColor, Clear(ClearType::All), MoveTo(0, 0));
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// State of the full-screen cookie browser
+struct Browser {
+    buckets: Vec<(Bucket, i64)>,
+    total_cookies: i64,
+    /// Index into the sidebar, where 0 is "All cookies"
+    bucket_index: usize,
+    cookies: Vec<CookieWithBucket>,
+    cookie_index: usize,
+    cookie_scroll: usize,
+    focus: Pane,
+    mode: Mode,
+    search: Option<String>,
+    /// Date range as typed, and what it parsed into
+    filter: Option<(String, dates::DateRange)>,
+    /// Where each page visited so far started, the current page last
+    page_starts: Vec<Option<db::PageCursor>>,
+    next_page: Option<db::PageCursor>,
+    pending_g: bool,
+    status: Option<(String, Color)>,
+    quit: bool,
+}
+
+/// Open the full-screen browser, optionally starting on a bucket.
+/// The caller is expected to have entered the alternate screen.
+pub async fn browse(store: &impl CookieStore, start_bucket: Option<i64>) -> Result<()> {
+    let mut browser = Browser {
+        buckets: Vec::new(),
+        total_cookies: 0,
+        bucket_index: 0,
+        cookies: Vec::new(),
+        cookie_index: 0,
+        cookie_scroll: 0,
+        focus: Pane::Cookies,
+        mode: Mode::Normal,
+        search: None,
+        filter: None,
+        page_starts: vec![None],
+        next_page: None,
+        pending_g: false,
+        status: None,
+        quit: false,
+    };
+
+    browser.load_buckets(store).await?;
+    if let Some(i) = start_bucket.and_then(|id| browser.buckets.iter().position(|(b, _)| b.id == id)) {
+        browser.bucket_index = i + 1;
+    }
+    browser.load_cookies(store).await?;
+
+    let _guard = RawModeGuard::enter()?;
+    let mut out = stdout();
+
+    while !browser.quit {
+        browser.draw(&mut out)?;
+
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                browser.status = None;
+                if let Err(e) = browser.handle_key(store, key).await {
+                    browser.status = Some((format!("Error: {}", e), Color::Red));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+impl Browser {
+    /// The bucket selected in the sidebar, or None for "All cookies"
+    fn selected_bucket(&self) -> Option<&Bucket> {
+        self.bucket_index
+            .checked_sub(1)
+            .and_then(|i| self.buckets.get(i))
+            .map(|(bucket, _)| bucket)
+    }
+
+    fn selected_entry(&self) -> Option<&CookieWithBucket> {
+        self.cookies.get(self.cookie_index)
+    }
+
+    fn selected_cookie(&self) -> Option<&Cookie> {
+        self.selected_entry().map(|entry| &entry.cookie)
+    }
+
+    async fn load_buckets(&mut self, store: &impl CookieStore) -> Result<()> {
+        self.buckets = store.get_buckets_with_stats()
+            .await?
+            .into_iter()
+            .map(|stats| (stats.bucket, stats.cookie_count))
+            .collect();
+        self.total_cookies = self.buckets.iter().map(|(_, count)| count).sum();
+        self.bucket_index = self.bucket_index.min(self.buckets.len());
+        Ok(())
+    }
+
+    /// Go back to the first page of cookies, e.g. when the bucket or filters change
+    fn first_page(&mut self) {
+        self.page_starts = vec![None];
+        self.cookie_index = 0;
+    }
+
+    /// Reload the cookie pane for the selected bucket, search, date filter and page.
+    /// Search results are ranked rather than ordered by date, so they aren't paged.
+    async fn load_cookies(&mut self, store: &impl CookieStore) -> Result<()> {
+        let bucket_id = self.selected_bucket().map(|b| b.id);
+        let range = self.filter.as_ref().map(|(_, range)| *range).unwrap_or_default();
+        let query = db::CookieQuery::new().bucket(bucket_id).since(range.since).until(range.until);
+
+        self.cookies = match &self.search {
+            Some(text) => store.search_cookies(text, None)
+                .await?
+                .into_iter()
+                .filter(|r| bucket_id.is_none_or(|id| r.cookie.bucket_id == id) && query.in_range(&r.cookie))
+                .map(|r| CookieWithBucket { cookie: r.cookie, bucket_name: r.bucket_name })
+                .collect(),
+            None => loop {
+                let start = self.page_starts.last().copied().flatten();
+                let page = store.get_cookie_page(&query, start, PAGE_SIZE).await?;
+                // Deleting the last cookie on a page leaves nothing to show, so step back
+                if page.cookies.is_empty() && self.page_starts.len() > 1 {
+                    self.page_starts.pop();
+                    continue;
+                }
+                self.next_page = page.next;
+                break page.cookies;
+            },
+        };
+        if self.search.is_some() {
+            self.next_page = None;
+        }
+
+        self.cookie_index = self.cookie_index.min(self.cookies.len().saturating_sub(1));
+        Ok(())
+    }
+
+    async fn reload(&mut self, store: &impl CookieStore) -> Result<()> {
+        self.load_buckets(store).await?;
+        self.load_cookies(store).await
+    }
+
+    // ============ INPUT ============
+
+    async fn handle_key(&mut self, store: &impl CookieStore, key: KeyEvent) -> Result<()> {
+        match self.mode.clone() {
+            Mode::Normal => self.handle_normal_key(store, key).await,
+            Mode::Input { kind, buffer } => self.handle_input_key(store, key, kind, buffer).await,
+            Mode::ConfirmDelete(id) => {
+                self.mode = Mode::Normal;
+                if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                    store.delete_cookie(id).await?;
+                    let synced = store.sync().await;
+                    self.reload(store).await?;
+                    self.report_saved("Cookie deleted".to_string(), synced);
+                }
+                Ok(())
+            }
+            Mode::MoveCookie { id, target } => self.handle_move_key(store, key, id, target).await,
+        }
+    }
+
+    async fn handle_normal_key(&mut self, store: &impl CookieStore, key: KeyEvent) -> Result<()> {
+        let pending_g = std::mem::take(&mut self.pending_g);
+
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            KeyCode::Esc => {
+                if self.search.take().is_some() || self.filter.take().is_some() {
+                    self.first_page();
+                    self.load_cookies(store).await?;
+                } else {
+                    self.quit = true;
+                }
+            }
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Pane::Buckets => Pane::Cookies,
+                    Pane::Cookies => Pane::Buckets,
+                };
+            }
+            KeyCode::Char('h') | KeyCode::Left => self.focus = Pane::Buckets,
+            KeyCode::Char('l') | KeyCode::Right | KeyCode::Enter => self.focus = Pane::Cookies,
+            KeyCode::Char('j') | KeyCode::Down => self.move_selection(store, 1).await?,
+            KeyCode::Char('k') | KeyCode::Up => self.move_selection(store, -1).await?,
+            KeyCode::PageDown => self.move_selection(store, 10).await?,
+            KeyCode::PageUp => self.move_selection(store, -10).await?,
+            KeyCode::Char('g') if pending_g => self.move_selection(store, isize::MIN).await?,
+            KeyCode::Char('g') => self.pending_g = true,
+            KeyCode::Char('G') => self.move_selection(store, isize::MAX).await?,
+            KeyCode::Char('/') => {
+                self.mode = Mode::Input {
+                    kind: InputKind::Search,
+                    buffer: self.search.clone().unwrap_or_default(),
+                };
+            }
+            KeyCode::Char('n') => {
+                if let Some(next) = self.next_page {
+                    self.page_starts.push(Some(next));
+                    self.cookie_index = 0;
+                    self.load_cookies(store).await?;
+                }
+            }
+            KeyCode::Char('p') if self.page_starts.len() > 1 => {
+                self.page_starts.pop();
+                self.cookie_index = 0;
+                self.load_cookies(store).await?;
+            }
+            KeyCode::Char('f') => {
+                self.mode = Mode::Input {
+                    kind: InputKind::Filter,
+                    buffer: self.filter.as_ref().map(|(text, _)| text.clone()).unwrap_or_default(),
+                };
+            }
+            KeyCode::Char('a') => {
+                if self.selected_bucket().is_none() {
+                    self.status = Some(("Pick a bucket on the left to add a cookie to".to_string(), Color::Yellow));
+                } else {
+                    self.mode = Mode::Input {
+                        kind: InputKind::AddCookie,
+                        buffer: String::new(),
+                    };
+                }
+            }
+            KeyCode::Char('e') => {
+                if let Some(cookie) = self.selected_cookie() {
+                    self.mode = Mode::Input {
+                        kind: InputKind::EditCookie(cookie.id),
+                        buffer: cookie.content.clone(),
+                    };
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(cookie) = self.selected_cookie() {
+                    self.mode = Mode::ConfirmDelete(cookie.id);
+                }
+            }
+            KeyCode::Char('m') => {
+                if let Some(cookie) = self.selected_cookie() {
+                    let target = self.buckets.iter().position(|(b, _)| b.id == cookie.bucket_id).unwrap_or(0);
+                    self.mode = Mode::MoveCookie { id: cookie.id, target };
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Move the selection in the focused pane by `delta` rows, clamping at the ends
+    async fn move_selection(&mut self, store: &impl CookieStore, delta: isize) -> Result<()> {
+        match self.focus {
+            Pane::Buckets => {
+                let new_index = step(self.bucket_index, delta, self.buckets.len() + 1);
+                if new_index != self.bucket_index {
+                    self.bucket_index = new_index;
+                    self.first_page();
+                    self.load_cookies(store).await?;
+                }
+            }
+            Pane::Cookies => {
+                self.cookie_index = step(self.cookie_index, delta, self.cookies.len());
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_input_key(
+        &mut self,
+        store: &impl CookieStore,
+        key: KeyEvent,
+        kind: InputKind,
+        mut buffer: String,
+    ) -> Result<()> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => {
+                self.mode = Mode::Normal;
+                return Ok(());
+            }
+            KeyCode::Char('c') if ctrl => {
+                self.mode = Mode::Normal;
+                return Ok(());
+            }
+            KeyCode::Enter => {
+                self.mode = Mode::Normal;
+                return self.submit_input(store, kind, buffer.trim()).await;
+            }
+            KeyCode::Backspace => {
+                if let Some((start, _)) = buffer.grapheme_indices(true).next_back() {
+                    buffer.truncate(start);
+                }
+            }
+            KeyCode::Char('u') if ctrl => buffer.clear(),
+            // Other control keys aren't text
+            KeyCode::Char(c) if !ctrl => buffer.push(c),
+            _ => {}
+        }
+
+        self.mode = Mode::Input { kind, buffer };
+        Ok(())
+    }
+
+    async fn submit_input(&mut self, store: &impl CookieStore, kind: InputKind, text: &str) -> Result<()> {
+        match kind {
+            InputKind::Search => {
+                self.search = (!text.is_empty()).then(|| text.to_string());
+                self.first_page();
+                self.focus = Pane::Cookies;
+                self.load_cookies(store).await?;
+            }
+            InputKind::Filter => {
+                self.filter = if text.is_empty() {
+                    None
+                } else {
+                    Some((text.to_string(), dates::parse_range(text)?))
+                };
+                self.first_page();
+                self.focus = Pane::Cookies;
+                self.load_cookies(store).await?;
+            }
+            InputKind::AddCookie => {
+                let Some(bucket_id) = self.selected_bucket().map(|b| b.id) else {
+                    return Ok(());
+                };
+                let id = store.create_cookie(bucket_id, text).await?;
+                let synced = store.sync().await;
+                self.search = None;
+                self.first_page();
+                self.reload(store).await?;
+                self.cookie_index = self.cookies.iter().position(|entry| entry.cookie.id == id).unwrap_or(0);
+                self.focus = Pane::Cookies;
+                self.report_saved("Cookie added!".to_string(), synced);
+            }
+            InputKind::EditCookie(id) => {
+                store.update_cookie_content(id, text).await?;
+                let synced = store.sync().await;
+                self.reload(store).await?;
+                self.report_saved("Cookie updated".to_string(), synced);
+            }
+        }
+        Ok(())
+    }
+
+    /// Report a change that has been saved. A sync that failed after it is left pending
+    /// for the next one, so it turns the report into a warning rather than an error.
+    fn report_saved(&mut self, message: String, synced: Result<()>) {
+        self.status = Some(match synced {
+            Ok(()) => (message, Color::Green),
+            Err(e) => (format!("{} (not synced yet: {:#})", message, e), Color::Yellow),
+        });
+    }
+
+    /// While moving a cookie, j/k pick the target bucket in the sidebar and Enter moves it there
+    async fn handle_move_key(
+        &mut self,
+        store: &impl CookieStore,
+        key: KeyEvent,
+        id: i64,
+        target: usize,
+    ) -> Result<()> {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::Normal,
+            KeyCode::Char('j') | KeyCode::Down => {
+                let target = step(target, 1, self.buckets.len());
+                self.mode = Mode::MoveCookie { id, target };
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let target = step(target, -1, self.buckets.len());
+                self.mode = Mode::MoveCookie { id, target };
+            }
+            KeyCode::Enter => {
+                self.mode = Mode::Normal;
+                if let Some((bucket, _)) = self.buckets.get(target).cloned() {
+                    store.move_cookie_to_bucket(id, bucket.id).await?;
+                    let synced = store.sync().await;
+                    self.reload(store).await?;
+                    self.report_saved(format!("Cookie moved to \"{}\"", bucket.name), synced);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // ============ DRAWING ============
+
+    fn draw(&mut self, out: &mut Stdout) -> Result<()> {
+        let (cols, rows) = terminal::size()?;
+        queue!(out, MoveTo(0, 0), ResetColor)?;
+
+        if cols < MIN_COLS || rows < MIN_ROWS {
+            queue!(out, Clear(ClearType::All), Print("Terminal too small for the browser (q to quit)"))?;
+            out.flush()?;
+            return Ok(());
+        }
+
+        let sidebar_width = (cols / 4).clamp(18, 28);
+        let main_x = sidebar_width;
+        let main_width = cols - sidebar_width;
+        let list_height = rows - DETAIL_HEIGHT - 1;
+
+        self.draw_sidebar(out, 0, 0, sidebar_width, rows - 1)?;
+        self.draw_cookie_list(out, main_x, 0, main_width, list_height)?;
+        self.draw_detail(out, main_x, list_height, main_width, DETAIL_HEIGHT)?;
+        self.draw_status_line(out, rows - 1, cols)?;
+
+        out.flush()?;
+        Ok(())
+    }
+
+    fn draw_sidebar(&self, out: &mut Stdout, x: u16, y: u16, width: u16, height: u16) -> Result<()> {
+        // While moving a cookie the sidebar picks the destination instead of filtering the list
+        let (title, highlighted, focused) = match self.mode {
+            Mode::MoveCookie { target, .. } => ("Move to…", target + 1, true),
+            _ => ("Buckets", self.bucket_index, self.focus == Pane::Buckets),
+        };
+        draw_box(out, x, y, width, height, title, focused)?;
+
+        let inner = (width - 2) as usize;
+        let visible = (height - 2) as usize;
+        let scroll = scroll_for(highlighted, visible, 0);
+
+        let mut entries = vec![(format!("All cookies ({})", self.total_cookies), Color::White)];
+        entries.extend(self.buckets.iter().map(|(bucket, count)| {
+            (format!("{} ({})", bucket.name, count), crossterm_color(bucket.id))
+        }));
+
+        for row in 0..visible {
+            queue!(out, MoveTo(x + 1, y + 1 + row as u16))?;
+            let Some((label, color)) = entries.get(scroll + row) else {
+                queue!(out, Print(" ".repeat(inner)))?;
+                continue;
+            };
+
+            let selected = scroll + row == highlighted;
+            if selected {
+                queue!(out, SetAttribute(if focused { Attribute::Reverse } else { Attribute::Bold }))?;
+            }
+            queue!(out, SetForegroundColor(*color), Print(fit(&format!(" {}", label), inner)))?;
+            queue!(out, SetAttribute(Attribute::Reset), ResetColor)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_cookie_list(&mut self, out: &mut Stdout, x: u16, y: u16, width: u16, height: u16) -> Result<()> {
+        let focused = self.focus == Pane::Cookies;
+        // The sidebar knows the full count unless a search or filter narrows it down
+        let count = match self.bucket_index.checked_sub(1).and_then(|i| self.buckets.get(i)) {
+            _ if self.search.is_some() || self.filter.is_some() => {
+                let more = if self.next_page.is_some() { "+" } else { "" };
+                format!("{}{}", self.cookies.len(), more)
+            }
+            Some((_, count)) => count.to_string(),
+            None => self.total_cookies.to_string(),
+        };
+        let mut title = match self.selected_bucket() {
+            Some(bucket) => format!("{} ({})", bucket.name, count),
+            None => format!("All cookies ({})", count),
+        };
+        if self.page_starts.len() > 1 || self.next_page.is_some() {
+            title = format!("{} page {}", title, self.page_starts.len());
+        }
+        if let Some(query) = &self.search {
+            title = format!("{} matching \"{}\"", title, query);
+        }
+        if let Some((range, _)) = &self.filter {
+            title = format!("{} [{}]", title, range);
+        }
+        draw_box(out, x, y, width, height, &title, focused)?;
+
+        let inner = (width - 2) as usize;
+        let visible = (height - 2) as usize;
+        self.cookie_scroll = scroll_for(self.cookie_index, visible, self.cookie_scroll);
+
+        if self.cookies.is_empty() {
+            let message = if self.search.is_some() || self.filter.is_some() {
+                " No cookies match. Press Esc to clear the search and filter."
+            } else {
+                " No cookies here yet. Press a to add one."
+            };
+            queue!(out, MoveTo(x + 1, y + 1), SetForegroundColor(Color::Yellow), Print(fit(message, inner)), ResetColor)?;
+            for row in 1..visible {
+                queue!(out, MoveTo(x + 1, y + 1 + row as u16), Print(" ".repeat(inner)))?;
+            }
+            return Ok(());
+        }
+
+        let show_bucket = self.selected_bucket().is_none();
+
+        for row in 0..visible {
+            queue!(out, MoveTo(x + 1, y + 1 + row as u16))?;
+            let index = self.cookie_scroll + row;
+            let Some(CookieWithBucket { cookie, bucket_name }) = self.cookies.get(index) else {
+                queue!(out, Print(" ".repeat(inner)))?;
+                continue;
+            };
+
+            if index == self.cookie_index {
+                queue!(out, SetAttribute(if focused { Attribute::Reverse } else { Attribute::Bold }))?;
+            }
+
+            let date = format!(" {}  ", cookie.formatted_created_at());
+            queue!(out, SetForegroundColor(Color::DarkGrey), Print(&date))?;
+            let mut used = date.width();
+
+            if show_bucket {
+                let bucket = fit(bucket_name, 12);
+                queue!(out, SetForegroundColor(crossterm_color(cookie.bucket_id)), Print(&bucket), Print("  "))?;
+                used += 14;
+            }
+
+            let content = cookie.content.replace('\n', " ");
+            queue!(out, SetForegroundColor(Color::White), Print(fit(&content, inner.saturating_sub(used))))?;
+            queue!(out, SetAttribute(Attribute::Reset), ResetColor)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_detail(&self, out: &mut Stdout, x: u16, y: u16, width: u16, height: u16) -> Result<()> {
+        draw_box(out, x, y, width, height, "Cookie", false)?;
+
+        let inner = (width - 2) as usize;
+        let visible = (height - 2) as usize;
+        let mut lines: Vec<(String, Color)> = Vec::new();
+
+        if let Some(CookieWithBucket { cookie, bucket_name }) = self.selected_entry() {
+            let tags = Tag::parse_hashtags(&cookie.content);
+            let mut meta = format!(" #{} · {} · {}", cookie.id, bucket_name, cookie.formatted_created_at());
+            if !tags.is_empty() {
+                let tags: Vec<String> = tags.iter().map(|t| format!("#{}", t)).collect();
+                meta = format!("{} · {}", meta, tags.join(" "));
+            }
+
+            let text_lines = visible.saturating_sub(2);
+            lines.extend(
+                wrap(&cookie.content, inner - 2)
+                    .into_iter()
+                    .take(text_lines)
+                    .map(|line| (format!(" {}", line), Color::White)),
+            );
+            while lines.len() < text_lines {
+                lines.push((String::new(), Color::White));
+            }
+            lines.push((String::new(), Color::White));
+            lines.push((meta, crossterm_color(cookie.bucket_id)));
+        }
+
+        for row in 0..visible {
+            let (text, color) = lines.get(row).cloned().unwrap_or((String::new(), Color::White));
+            queue!(
+                out,
+                MoveTo(x + 1, y + 1 + row as u16),
+                SetForegroundColor(color),
+                Print(fit(&text, inner)),
+                ResetColor
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_status_line(&self, out: &mut Stdout, y: u16, width: u16) -> Result<()> {
+        let width = width as usize;
+        queue!(out, MoveTo(0, y))?;
+
+        let (text, color) = match &self.mode {
+            Mode::Input { kind, buffer } => {
+                let prompt = match kind {
+                    InputKind::Search => "Search: ".to_string(),
+                    InputKind::Filter => "Dates (since..until, e.g. last monday..today): ".to_string(),
+                    InputKind::AddCookie => format!("New cookie ({} left): ", db::cookie_room_left(buffer)),
+                    InputKind::EditCookie(_) => format!("Edit ({} left): ", db::cookie_room_left(buffer)),
+                };
+                // Keep the end of long input visible, next to the cursor
+                let room = width.saturating_sub(prompt.width() + 1);
+                let shown = tail(buffer, room);
+                (format!("{}{}█", prompt, shown), Color::White)
+            }
+            Mode::ConfirmDelete(_) => ("Delete this cookie? (y/n)".to_string(), Color::Red),
+            Mode::MoveCookie { .. } => ("Pick a bucket with j/k, Enter to move, Esc to cancel".to_string(), Color::Cyan),
+            Mode::Normal => match &self.status {
+                Some((message, color)) => (message.clone(), *color),
+                None => (
+                    "j/k move  gg/G top/bottom  Tab pane  / search  f dates  n/p page  a add  e edit  m move  d delete  q quit".to_string(),
+                    Color::DarkGrey,
+                ),
+            },
+        };
+
+        queue!(out, SetForegroundColor(color), Print(fit(&text, width)), ResetColor)?;
+        Ok(())
+    }
+}
+
+/// Move an index by `delta` within `0..len`, clamping at both ends
+fn step(index: usize, delta: isize, len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    index.saturating_add_signed(delta).min(len - 1)
+}
+
+/// Adjust a scroll offset so that `index` stays within `visible` rows
+fn scroll_for(index: usize, visible: usize, scroll: usize) -> usize {
+    if visible == 0 {
+        0
+    } else if index < scroll {
+        index
+    } else if index >= scroll + visible {
+        index + 1 - visible
+    } else {
+        scroll
+    }
+}
+
+/// Truncate text to at most `width` terminal columns, marking the cut with an ellipsis
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    if width > 0 {
+        out.push('…');
+    }
+    out
+}
+
+/// Truncate or pad text to exactly `width` terminal columns
+fn fit(text: &str, width: usize) -> String {
+    let mut out = truncate(text, width);
+    let used = out.width();
+    if used < width {
+        out.push_str(&" ".repeat(width - used));
+    }
+    out
+}
+
+/// The last `width` columns of text
+fn tail(text: &str, width: usize) -> String {
+    let mut chars: Vec<char> = Vec::new();
+    let mut used = 0;
+    for c in text.chars().rev() {
+        let w = c.width().unwrap_or(0);
+        if used + w > width {
+            break;
+        }
+        chars.push(c);
+        used += w;
+    }
+    chars.into_iter().rev().collect()
+}
+
+/// Word-wrap text to lines of at most `width` columns
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        let needed = if line.is_empty() { word.width() } else { line.width() + 1 + word.width() };
+        if needed > width && !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Draw a box border with a title, highlighted when its pane has focus
+fn draw_box(out: &mut Stdout, x: u16, y: u16, width: u16, height: u16, title: &str, focused: bool) -> Result<()> {
+    let color = if focused { Color::Cyan } else { Color::DarkGrey };
+    let inner = (width - 2) as usize;
+
+    let title = truncate(&format!(" {} ", title), inner.saturating_sub(2));
+    let top = format!("┌─{}{}┐", title, "─".repeat(inner.saturating_sub(title.width() + 1)));
+
+    queue!(out, SetForegroundColor(color), MoveTo(x, y), Print(top))?;
+    for row in 1..height - 1 {
+        queue!(out, MoveTo(x, y + row), Print("│"), MoveTo(x + width - 1, y + row), Print("│"))?;
+    }
+    queue!(
+        out,
+        MoveTo(x, y + height - 1),
+        Print(format!("└{}┘", "─".repeat(inner))),
+        ResetColor
+    )?;
+
+    Ok(())
+}
+
+/// The crossterm equivalent of a bucket's colour in the menus
+fn crossterm_color(bucket_id: i64) -> Color {
+    if settings::current().theme == Theme::Plain {
+        return Color::Reset;
+    }
+    match get_bucket_color(bucket_id) {
+        colored::Color::BrightCyan => Color::Cyan,
+        colored::Color::BrightMagenta => Color::Magenta,
+        colored::Color::BrightYellow => Color::Yellow,
+        colored::Color::BrightGreen => Color::Green,
+        colored::Color::BrightBlue => Color::Blue,
+        _ => Color::White,
+    }
+}
@@ -0,0 +1,69 @@
+//! Dates typed the way people type them, relative to a fixed day.
+
+use chrono::NaiveDate;
+use cookie_jar::dates::parse_date;
+
+/// Thursday 2026-10-15
+fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 10, 15).unwrap()
+}
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+fn parse(input: &str) -> NaiveDate {
+    parse_date(input, today()).unwrap()
+}
+
+#[test]
+fn iso_dates_and_yesterday() {
+    assert_eq!(parse("2026-09-30"), date(2026, 9, 30));
+    assert_eq!(parse("today"), today());
+    assert_eq!(parse("Yesterday"), date(2026, 10, 14));
+}
+
+#[test]
+fn weekdays_are_this_week_unless_last() {
+    assert_eq!(parse("monday"), date(2026, 10, 12));
+    assert_eq!(parse("this friday"), date(2026, 10, 16));
+    assert_eq!(parse("last monday"), date(2026, 10, 12));
+    assert_eq!(parse("last thursday"), date(2026, 10, 8));
+}
+
+#[test]
+fn units_ago() {
+    assert_eq!(parse("3 days ago"), date(2026, 10, 12));
+    assert_eq!(parse("1 day ago"), date(2026, 10, 14));
+    assert_eq!(parse("2 weeks ago"), date(2026, 10, 1));
+    assert_eq!(parse("1 month ago"), date(2026, 9, 15));
+    assert_eq!(parse("2 years ago"), date(2024, 10, 15));
+}
+
+#[test]
+fn month_and_day_without_a_year_is_the_most_recent_one() {
+    assert_eq!(parse("sep 30"), date(2026, 9, 30));
+    assert_eq!(parse("oct 15"), today());
+    assert_eq!(parse("dec 25"), date(2025, 12, 25));
+    assert_eq!(parse("30 september 2024"), date(2024, 9, 30));
+}
+
+#[test]
+fn month_and_day_rolls_back_a_year_once_past_today() {
+    let new_year = date(2027, 1, 10);
+
+    assert_eq!(parse_date("sep 30", new_year).unwrap(), date(2026, 9, 30));
+}
+
+#[test]
+fn huge_amounts_ago_are_rejected_rather_than_overflowing() {
+    for input in ["4000000000 days ago", "4000000000 weeks ago", "4000000000 months ago", "4000000000 years ago"] {
+        assert!(parse_date(input, today()).is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn nonsense_is_rejected() {
+    assert!(parse_date("someday", today()).is_err());
+    assert!(parse_date("3 fortnights ago", today()).is_err());
+}
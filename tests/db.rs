@@ -0,0 +1,302 @@
+//! The database layer against a local libsql file; none of this needs Turso.
+
+use cookie_jar::config::settings::{self, Settings};
+use cookie_jar::db::{self, migrations, BucketCookies, Database};
+use cookie_jar::error::{BucketRef, CookieJarError};
+use tempfile::TempDir;
+
+/// A fresh jar in its own directory, with the schema in place.
+/// The directory and database are returned so they outlive the connection.
+async fn open_jar() -> (TempDir, Database, libsql::Connection) {
+    let (dir, database, conn) = open_empty_jar().await;
+    db::init_schema(&conn).await.unwrap();
+    (dir, database, conn)
+}
+
+/// A fresh jar without any schema, for tests that lay down migrations themselves
+async fn open_empty_jar() -> (TempDir, Database, libsql::Connection) {
+    // Whatever config.toml the machine running the tests has, these expect the defaults
+    settings::install(Settings::defaults());
+    let dir = tempfile::tempdir().unwrap();
+    let database = Database::new_local(dir.path().join("cookie_jar.db")).await.unwrap();
+    let conn = database.connect().unwrap();
+    (dir, database, conn)
+}
+
+#[tokio::test]
+async fn init_schema_applies_every_migration() {
+    let (_dir, _database, conn) = open_jar().await;
+
+    assert_eq!(migrations::current_version(&conn).await.unwrap(), migrations::latest_version());
+    assert!(db::get_all_buckets(&conn).await.unwrap().is_empty());
+    assert!(db::get_all_cookies(&conn).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn init_schema_is_idempotent() {
+    let (_dir, _database, conn) = open_jar().await;
+    let bucket = db::create_bucket(&conn, "Work").await.unwrap();
+    db::create_cookie(&conn, bucket.id, "Shipped the release").await.unwrap();
+
+    db::init_schema(&conn).await.unwrap();
+
+    assert_eq!(migrations::current_version(&conn).await.unwrap(), migrations::latest_version());
+    assert_eq!(db::get_all_cookies(&conn).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn create_bucket_returns_the_new_bucket() {
+    let (_dir, _database, conn) = open_jar().await;
+
+    let work = db::create_bucket(&conn, "Work").await.unwrap();
+    let fitness = db::create_bucket(&conn, "Fitness").await.unwrap();
+
+    assert_eq!(work.name, "Work");
+    assert_ne!(work.id, fitness.id);
+    let found = db::get_bucket_by_name(&conn, "Work").await.unwrap().unwrap();
+    assert_eq!(found.id, work.id);
+
+    let names: Vec<_> = db::get_all_buckets(&conn).await.unwrap().into_iter().map(|b| b.name).collect();
+    assert_eq!(names, ["Fitness", "Work"]);
+}
+
+#[tokio::test]
+async fn duplicate_bucket_names_hit_the_unique_constraint() {
+    let (_dir, _database, conn) = open_jar().await;
+    db::create_bucket(&conn, "Work").await.unwrap();
+
+    let err = db::create_bucket(&conn, "Work").await.unwrap_err();
+
+    assert_eq!(err.downcast_ref(), Some(&CookieJarError::BucketExists("Work".to_string())));
+    assert_eq!(db::get_all_buckets(&conn).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn renaming_onto_a_taken_name_hits_the_unique_constraint() {
+    let (_dir, _database, conn) = open_jar().await;
+    let work = db::create_bucket(&conn, "Work").await.unwrap();
+    db::create_bucket(&conn, "Fitness").await.unwrap();
+
+    let err = db::rename_bucket(&conn, work.id, "Fitness").await.unwrap_err();
+
+    assert_eq!(err.downcast_ref(), Some(&CookieJarError::BucketExists("Fitness".to_string())));
+}
+
+#[tokio::test]
+async fn missing_buckets_are_reported_as_not_found() {
+    let (_dir, _database, conn) = open_jar().await;
+
+    let err = db::rename_bucket(&conn, 42, "Work").await.unwrap_err();
+
+    assert_eq!(err.downcast_ref(), Some(&CookieJarError::BucketNotFound(BucketRef::Id(42))));
+}
+
+#[tokio::test]
+async fn merging_needs_both_buckets_to_exist() {
+    let (_dir, _database, conn) = open_jar().await;
+    let work = db::create_bucket(&conn, "Work").await.unwrap();
+    db::create_cookie(&conn, work.id, "Shipped it").await.unwrap();
+
+    let missing_from = db::merge_buckets(&conn, 42, work.id).await.unwrap_err();
+    let missing_into = db::merge_buckets(&conn, work.id, 42).await.unwrap_err();
+
+    let not_found = CookieJarError::BucketNotFound(BucketRef::Id(42));
+    assert_eq!(missing_from.downcast_ref(), Some(&not_found));
+    assert_eq!(missing_into.downcast_ref(), Some(&not_found));
+    assert_eq!(db::count_cookies_in_bucket(&conn, work.id).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn deleting_a_bucket_into_a_missing_one_keeps_its_cookies() {
+    let (_dir, _database, conn) = open_jar().await;
+    let work = db::create_bucket(&conn, "Work").await.unwrap();
+    db::create_cookie(&conn, work.id, "Shipped it").await.unwrap();
+
+    let err = db::delete_bucket(&conn, work.id, BucketCookies::MoveTo(42)).await.unwrap_err();
+    let missing = db::delete_bucket(&conn, 43, BucketCookies::Delete).await.unwrap_err();
+
+    assert_eq!(err.downcast_ref(), Some(&CookieJarError::BucketNotFound(BucketRef::Id(42))));
+    assert_eq!(missing.downcast_ref(), Some(&CookieJarError::BucketNotFound(BucketRef::Id(43))));
+    assert_eq!(db::count_cookies_in_bucket(&conn, work.id).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn create_cookie_stores_content_in_its_bucket() {
+    let (_dir, _database, conn) = open_jar().await;
+    let bucket = db::create_bucket(&conn, "Work").await.unwrap();
+
+    let id = db::create_cookie(&conn, bucket.id, "Fixed the flaky build").await.unwrap();
+
+    let cookie = db::get_cookie(&conn, id).await.unwrap().unwrap();
+    assert_eq!(cookie.bucket_id, bucket.id);
+    assert_eq!(cookie.content, "Fixed the flaky build");
+}
+
+#[tokio::test]
+async fn create_cookie_accepts_exactly_300_characters() {
+    let (_dir, _database, conn) = open_jar().await;
+    let bucket = db::create_bucket(&conn, "Work").await.unwrap();
+
+    let id = db::create_cookie(&conn, bucket.id, &"a".repeat(300)).await.unwrap();
+
+    assert_eq!(db::get_cookie(&conn, id).await.unwrap().unwrap().content.len(), 300);
+}
+
+#[tokio::test]
+async fn create_cookie_rejects_empty_and_overlong_content() {
+    let (_dir, _database, conn) = open_jar().await;
+    let bucket = db::create_bucket(&conn, "Work").await.unwrap();
+
+    assert!(db::create_cookie(&conn, bucket.id, "").await.is_err());
+    let err = db::create_cookie(&conn, bucket.id, &"a".repeat(301)).await.unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&CookieJarError::CookieTooLong { length: 301, max: 300 }));
+    assert_eq!(db::count_cookies_in_bucket(&conn, bucket.id).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn cookie_length_counts_what_people_see_as_one_character() {
+    assert_eq!(db::cookie_length("cafe\u{301}"), 4);
+    assert_eq!(db::cookie_length("👨‍👩‍👧 ran 5k 🇳🇿"), 10);
+    assert_eq!(db::cookie_length("日本語"), 3);
+}
+
+#[tokio::test]
+async fn create_cookie_counts_emoji_as_single_characters() {
+    let (_dir, _database, conn) = open_jar().await;
+    let bucket = db::create_bucket(&conn, "Family").await.unwrap();
+    let family = "👨‍👩‍👧";
+
+    // 300 characters, but 2100 code points and 5400 bytes
+    let id = db::create_cookie(&conn, bucket.id, &family.repeat(300)).await.unwrap();
+    let err = db::create_cookie(&conn, bucket.id, &family.repeat(301)).await.unwrap_err();
+
+    assert_eq!(db::get_cookie(&conn, id).await.unwrap().unwrap().content, family.repeat(300));
+    assert_eq!(err.downcast_ref(), Some(&CookieJarError::CookieTooLong { length: 301, max: 300 }));
+}
+
+#[tokio::test]
+async fn the_schema_leaves_the_length_limit_to_the_app() {
+    let (_dir, _database, conn) = open_jar().await;
+    let bucket = db::create_bucket(&conn, "Work").await.unwrap();
+
+    // 200 characters, but 400 code points, which a length CHECK would have counted
+    let accented = "e\u{301}".repeat(200);
+    db::create_cookie(&conn, bucket.id, &accented).await.unwrap();
+    // Straight to SQL, past db::validate_cookie_content, even 301 plain letters go in
+    conn.execute(
+        "INSERT INTO cookies (bucket_id, content, created_at) VALUES (?1, ?2, 0)",
+        libsql::params![bucket.id, "a".repeat(301)],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(db::count_cookies_in_bucket(&conn, bucket.id).await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn rebuilding_the_cookies_table_keeps_cookies_searchable() {
+    let (_dir, _database, conn) = open_empty_jar().await;
+
+    // A jar as cj left it before cookie lengths were counted per character
+    migrations::current_version(&conn).await.unwrap();
+    for migration in migrations::MIGRATIONS.iter().filter(|m| m.version <= 5) {
+        conn.execute_batch(migration.sql).await.unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, ?2, 0)",
+            libsql::params![migration.version, migration.description],
+        )
+        .await
+        .unwrap();
+    }
+    let work = db::create_bucket(&conn, "Work").await.unwrap();
+    let kept = db::create_cookie(&conn, work.id, "Shipped the release #launch").await.unwrap();
+    let deleted = db::create_cookie(&conn, work.id, "Fixed the flaky build #ci").await.unwrap();
+    db::record_cookie_view(&conn, kept).await.unwrap();
+
+    db::init_schema(&conn).await.unwrap();
+    db::delete_cookie(&conn, deleted).await.unwrap();
+    let added = db::create_cookie(&conn, work.id, "Gave the release talk").await.unwrap();
+
+    assert_eq!(migrations::current_version(&conn).await.unwrap(), migrations::latest_version());
+    assert!(added > deleted);
+    let found: Vec<_> = db::search_cookies(&conn, "release", None).await.unwrap().into_iter().map(|r| r.cookie.id).collect();
+    assert_eq!(found.len(), 2);
+    assert!(found.contains(&kept) && found.contains(&added));
+    assert!(db::search_cookies(&conn, "flaky", None).await.unwrap().is_empty());
+    let tags: Vec<_> = db::get_tag_counts(&conn).await.unwrap().into_iter().map(|(tag, uses)| (tag.name, uses)).collect();
+    assert_eq!(tags, [("launch".to_string(), 1)]);
+    let mut views = conn.query("SELECT cookie_id FROM cookie_views", ()).await.unwrap();
+    assert_eq!(views.next().await.unwrap().unwrap().get::<i64>(0).unwrap(), kept);
+    assert!(views.next().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn get_all_cookies_lists_newest_first() {
+    let (_dir, _database, conn) = open_jar().await;
+    let work = db::create_bucket(&conn, "Work").await.unwrap();
+    let fitness = db::create_bucket(&conn, "Fitness").await.unwrap();
+
+    db::create_cookie_at(&conn, work.id, "middle", 2_000).await.unwrap();
+    db::create_cookie_at(&conn, fitness.id, "oldest", 1_000).await.unwrap();
+    db::create_cookie_at(&conn, work.id, "newest", 3_000).await.unwrap();
+
+    let cookies = db::get_all_cookies(&conn).await.unwrap();
+    let contents: Vec<_> = cookies.iter().map(|c| c.content.as_str()).collect();
+    assert_eq!(contents, ["newest", "middle", "oldest"]);
+    assert!(cookies.windows(2).all(|pair| pair[0].created_at >= pair[1].created_at));
+}
+
+#[tokio::test]
+async fn count_cookies_in_bucket_counts_only_that_bucket() {
+    let (_dir, _database, conn) = open_jar().await;
+    let work = db::create_bucket(&conn, "Work").await.unwrap();
+    let fitness = db::create_bucket(&conn, "Fitness").await.unwrap();
+    let empty = db::create_bucket(&conn, "Kindness").await.unwrap();
+
+    for content in ["Shipped it", "Closed the deal", "Gave the talk"] {
+        db::create_cookie(&conn, work.id, content).await.unwrap();
+    }
+    db::create_cookie(&conn, fitness.id, "Ran a 10k").await.unwrap();
+
+    assert_eq!(db::count_cookies_in_bucket(&conn, work.id).await.unwrap(), 3);
+    assert_eq!(db::count_cookies_in_bucket(&conn, fitness.id).await.unwrap(), 1);
+    assert_eq!(db::count_cookies_in_bucket(&conn, empty.id).await.unwrap(), 0);
+    assert_eq!(db::count_cookies_in_bucket(&conn, 999).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn deleting_a_cookie_updates_the_count() {
+    let (_dir, _database, conn) = open_jar().await;
+    let bucket = db::create_bucket(&conn, "Work").await.unwrap();
+    let id = db::create_cookie(&conn, bucket.id, "Shipped it").await.unwrap();
+    db::create_cookie(&conn, bucket.id, "Closed the deal").await.unwrap();
+
+    db::delete_cookie(&conn, id).await.unwrap();
+
+    assert_eq!(db::count_cookies_in_bucket(&conn, bucket.id).await.unwrap(), 1);
+    assert!(db::get_cookie(&conn, id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn copying_a_jar_keeps_tags_and_views() {
+    let (_dir, _database, local) = open_jar().await;
+    let (_remote_dir, _remote_database, remote) = open_jar().await;
+    let work = db::create_bucket(&local, "Work").await.unwrap();
+    let talk = db::create_cookie(&local, work.id, "Gave a talk #speaking #conference").await.unwrap();
+    db::create_cookie(&local, work.id, "Fixed the build").await.unwrap();
+    db::record_cookie_view(&local, talk).await.unwrap();
+
+    let summary = db::copy_jar(&local, &remote).await.unwrap();
+    let again = db::copy_jar(&local, &remote).await.unwrap();
+
+    assert_eq!((summary.cookies_copied, again.cookies_copied, again.cookies_skipped), (2, 0, 2));
+    let tags: Vec<(String, i64)> = db::get_tag_counts(&remote).await.unwrap().into_iter().map(|(t, n)| (t.name, n)).collect();
+    assert_eq!(tags, vec![("conference".to_string(), 1), ("speaking".to_string(), 1)]);
+    let tagged = db::query_cookies(&remote, &db::CookieQuery::new().tag(Some("speaking"))).await.unwrap();
+    assert_eq!(tagged[0].content, "Gave a talk #speaking #conference");
+
+    let mut rows = remote.query("SELECT cookie_id FROM cookie_views", ()).await.unwrap();
+    let viewed: i64 = rows.next().await.unwrap().unwrap().get(0).unwrap();
+    assert_eq!(viewed, tagged[0].id);
+    assert!(rows.next().await.unwrap().is_none());
+}
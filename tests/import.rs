@@ -0,0 +1,64 @@
+//! Reading import files; none of this touches a jar.
+
+use cookie_jar::import::{self, ImportFormat};
+use cookie_jar::models::Bucket;
+
+/// Where each line of a plain-text import ends up, as (bucket, content)
+fn parse_text(text: &str, buckets: &[Bucket]) -> Vec<(String, String)> {
+    import::parse(text, ImportFormat::Text, "Imported", buckets)
+        .unwrap()
+        .into_iter()
+        .map(|entry| (entry.record.bucket, entry.record.content))
+        .collect()
+}
+
+fn entry(bucket: &str, content: &str) -> (String, String) {
+    (bucket.to_string(), content.to_string())
+}
+
+#[test]
+fn a_one_word_prefix_names_the_bucket() {
+    let entries = parse_text("Work: Shipped the release\n- Side-projects: Launched the blog\n\nNo bucket here\n", &[]);
+
+    assert_eq!(
+        entries,
+        vec![
+            entry("Work", "Shipped the release"),
+            entry("Side-projects", "Launched the blog"),
+            entry("Imported", "No bucket here"),
+        ]
+    );
+}
+
+#[test]
+fn colons_in_ordinary_sentences_stay_in_the_cookie() {
+    let text = "Fixed the bug: the parser no longer crashes\n\
+                https://example.com/talk went live\n\
+                Up at 6:30 for the run\n\
+                10:30 standup ran short\n";
+
+    assert_eq!(
+        parse_text(text, &[]),
+        vec![
+            entry("Imported", "Fixed the bug: the parser no longer crashes"),
+            entry("Imported", "https://example.com/talk went live"),
+            entry("Imported", "Up at 6:30 for the run"),
+            entry("Imported", "10:30 standup ran short"),
+        ]
+    );
+}
+
+#[test]
+fn an_existing_bucket_can_be_named_even_with_spaces() {
+    let buckets = [Bucket::new(1, "Side projects".to_string(), 0)];
+
+    let entries = parse_text("Side projects: Launched the blog\nOther projects: Still a sentence\n", &buckets);
+
+    assert_eq!(
+        entries,
+        vec![
+            entry("Side projects", "Launched the blog"),
+            entry("Imported", "Other projects: Still a sentence"),
+        ]
+    );
+}
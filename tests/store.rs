@@ -0,0 +1,282 @@
+//! The same scenarios run against a libsql jar and a jar in memory. Anything the
+//! menu or a command can see should come out the same from both.
+
+use cookie_jar::config::settings::{self, Settings};
+use cookie_jar::db::{self, BucketCookies, CookieQuery, Database};
+use cookie_jar::error::{BucketRef, CookieJarError};
+use cookie_jar::import::{self, ImportFormat};
+use cookie_jar::store::memory::MemoryStore;
+use cookie_jar::store::{CookieStore, LibsqlStore};
+use std::collections::BTreeSet;
+use tempfile::TempDir;
+
+/// A fresh libsql jar in its own directory, which is returned so it outlives the store
+async fn libsql_store() -> (TempDir, LibsqlStore) {
+    settings::install(Settings::defaults());
+    let dir = tempfile::tempdir().unwrap();
+    let database = Database::new_local(dir.path().join("cookie_jar.db")).await.unwrap();
+    let store = LibsqlStore::new(database).unwrap();
+    db::init_schema(store.conn()).await.unwrap();
+    (dir, store)
+}
+
+/// Run a scenario against both stores, check they agree, and return what they agreed on
+macro_rules! same_on_both {
+    ($scenario:ident) => {{
+        let (_dir, libsql) = libsql_store().await;
+        let expected = $scenario(&libsql).await;
+        assert_eq!($scenario(&MemoryStore::new()).await, expected, "MemoryStore disagrees with LibsqlStore");
+        expected
+    }};
+}
+
+/// How a call turned out, with failures compared by the error the caller would handle
+fn outcome<T>(result: anyhow::Result<T>) -> Result<T, String> {
+    result.map_err(|e| match e.downcast_ref::<CookieJarError>() {
+        Some(error) => format!("{:?}", error),
+        None => e.to_string(),
+    })
+}
+
+async fn numbered_ids(store: &impl CookieStore) -> (Vec<(i64, String)>, Vec<i64>) {
+    let work = store.create_bucket("Work").await.unwrap();
+    let home = store.create_bucket("Home").await.unwrap();
+    store.delete_bucket(home.id, BucketCookies::Delete).await.unwrap();
+    let play = store.create_bucket("Play").await.unwrap();
+
+    let first = store.create_cookie(work.id, "Shipped it").await.unwrap();
+    store.delete_cookie(first).await.unwrap();
+    store.create_cookie(play.id, "Won the match").await.unwrap();
+
+    let buckets = store.get_all_buckets().await.unwrap().into_iter().map(|b| (b.id, b.name)).collect();
+    let cookies = store.query_cookies(&CookieQuery::new()).await.unwrap().into_iter().map(|c| c.id).collect();
+    (buckets, cookies)
+}
+
+#[tokio::test]
+async fn ids_are_never_reused() {
+    let (buckets, cookies) = same_on_both!(numbered_ids);
+
+    assert_eq!(buckets, vec![(3, "Play".to_string()), (1, "Work".to_string())]);
+    assert_eq!(cookies, vec![2]);
+}
+
+async fn writes_into_a_missing_bucket(store: &impl CookieStore) -> Vec<Result<(), String>> {
+    let work = store.create_bucket("Work").await.unwrap();
+    let cookie = store.create_cookie(work.id, "Shipped it").await.unwrap();
+
+    let outcomes = vec![
+        outcome(store.create_cookie(42, "Lost").await.map(drop)),
+        outcome(store.move_cookie_to_bucket(cookie, 42).await),
+        outcome(store.merge_buckets(work.id, 42).await.map(drop)),
+        outcome(store.merge_buckets(42, work.id).await.map(drop)),
+        outcome(store.delete_bucket(work.id, BucketCookies::MoveTo(42)).await),
+        outcome(store.delete_bucket(42, BucketCookies::Delete).await),
+        outcome(store.rename_bucket(42, "Elsewhere").await),
+    ];
+    assert_eq!(store.count_cookies_in_bucket(work.id).await.unwrap(), 1);
+    outcomes
+}
+
+#[tokio::test]
+async fn writes_into_a_missing_bucket_fail_the_same_way() {
+    let outcomes = same_on_both!(writes_into_a_missing_bucket);
+
+    let not_found = format!("{:?}", CookieJarError::BucketNotFound(BucketRef::Id(42)));
+    assert!(outcomes.iter().all(|outcome| outcome.as_ref().unwrap_err() == &not_found), "{:?}", outcomes);
+}
+
+async fn missing_cookies(store: &impl CookieStore) -> Vec<Result<(), String>> {
+    let work = store.create_bucket("Work").await.unwrap();
+    vec![
+        outcome(store.update_cookie_content(42, "Rewritten").await),
+        outcome(store.move_cookie_to_bucket(42, work.id).await),
+        outcome(store.delete_cookie(42).await),
+    ]
+}
+
+#[tokio::test]
+async fn missing_cookies_fail_the_same_way() {
+    let outcomes = same_on_both!(missing_cookies);
+
+    assert!(outcomes.iter().all(|outcome| outcome.as_ref().unwrap_err() == "Cookie #42 does not exist"));
+}
+
+async fn edit_into_a_missing_bucket(store: &impl CookieStore) -> (Result<(), String>, String, Vec<String>) {
+    let work = store.create_bucket("Work").await.unwrap();
+    let cookie = store.create_cookie(work.id, "Gave a talk #speaking").await.unwrap();
+
+    let result = outcome(store.edit_cookie(cookie, Some("Gave a keynote #keynote"), Some(42)).await);
+
+    let content = store.get_cookie(cookie).await.unwrap().unwrap().content;
+    let tags = store.get_tag_counts().await.unwrap().into_iter().map(|(tag, _)| tag.name).collect();
+    (result, content, tags)
+}
+
+#[tokio::test]
+async fn a_failed_edit_changes_nothing() {
+    let (result, content, tags) = same_on_both!(edit_into_a_missing_bucket);
+
+    assert_eq!(result, Err(format!("{:?}", CookieJarError::BucketNotFound(BucketRef::Id(42)))));
+    assert_eq!(content, "Gave a talk #speaking");
+    assert_eq!(tags, vec!["speaking".to_string()]);
+}
+
+async fn bucket_lifecycle(store: &impl CookieStore) -> Vec<(String, i64)> {
+    let work = store.create_bucket("Work").await.unwrap();
+    let side = store.create_bucket("Side projects").await.unwrap();
+    let home = store.create_bucket("Home").await.unwrap();
+    for content in ["Shipped it", "Fixed the build"] {
+        store.create_cookie(work.id, content).await.unwrap();
+    }
+    store.create_cookie(side.id, "Launched the blog").await.unwrap();
+    store.create_cookie(home.id, "Fixed the sink").await.unwrap();
+
+    assert_eq!(store.merge_buckets(side.id, work.id).await.unwrap(), 1);
+    store.rename_bucket(work.id, "Projects").await.unwrap();
+    assert!(outcome(store.rename_bucket(home.id, "Projects").await).is_err());
+    store.delete_bucket(home.id, BucketCookies::MoveTo(work.id)).await.unwrap();
+
+    store
+        .get_buckets_with_stats()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|stats| (stats.bucket.name, stats.cookie_count))
+        .collect()
+}
+
+#[tokio::test]
+async fn buckets_merge_rename_and_delete_alike() {
+    let buckets = same_on_both!(bucket_lifecycle);
+
+    assert_eq!(buckets, vec![("Projects".to_string(), 4)]);
+}
+
+async fn tags_after_edits(store: &impl CookieStore) -> Vec<(String, i64)> {
+    let work = store.create_bucket("Work").await.unwrap();
+    let first = store.create_cookie(work.id, "Gave a talk #speaking").await.unwrap();
+    store.create_cookie(work.id, "Wrote it up #writing #speaking").await.unwrap();
+    store.update_cookie_content(first, "Gave a talk #conference").await.unwrap();
+
+    store
+        .get_tag_counts()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(tag, count)| (tag.name, count))
+        .collect()
+}
+
+#[tokio::test]
+async fn tags_follow_edits_alike() {
+    let tags = same_on_both!(tags_after_edits);
+
+    assert_eq!(
+        tags,
+        vec![
+            ("conference".to_string(), 1),
+            ("speaking".to_string(), 1),
+            ("writing".to_string(), 1),
+        ]
+    );
+}
+
+/// Which cookies each search finds. Only libsql ranks them, so the order isn't compared.
+async fn search_matches(store: &impl CookieStore) -> Vec<BTreeSet<i64>> {
+    let work = store.create_bucket("Work").await.unwrap();
+    for content in [
+        "Taught the team some rust and a little python over a long afternoon",
+        "Rust rust rust, all week",
+        "Paired on python",
+        "Rewrote the parser in Rust #rewrite",
+        "Ported the importer to Rust #rewrite",
+        "Reviewed a friend's rusty bike repair",
+    ] {
+        store.create_cookie(work.id, content).await.unwrap();
+    }
+
+    let mut matches = Vec::new();
+    for (query, tag) in [("rust", None), ("rust python", None), ("rust", Some("rewrite")), ("team rust", None), ("golang", None)] {
+        let results = store.search_cookies(query, tag).await.unwrap();
+        assert!(results.iter().all(|result| result.bucket_name == "Work"));
+        matches.push(results.into_iter().map(|result| result.cookie.id).collect());
+    }
+    matches
+}
+
+#[tokio::test]
+async fn search_finds_the_same_cookies() {
+    let matches = same_on_both!(search_matches);
+
+    assert_eq!(matches[0], BTreeSet::from([1, 2, 4, 5, 6]));
+    assert_eq!(matches[1], BTreeSet::from([1]));
+    assert_eq!(matches[2], BTreeSet::from([4, 5]));
+    assert_eq!(matches[3], BTreeSet::from([1]));
+    assert!(matches[4].is_empty());
+}
+
+async fn imported(store: &impl CookieStore) -> (Vec<(String, i64)>, usize, Vec<String>) {
+    let work = store.create_bucket("Work").await.unwrap();
+    store.create_cookie(work.id, "Shipped it").await.unwrap();
+
+    let text = "Work: Shipped it\nHome: Fixed the sink #diy\nCleared the inbox\n";
+    let entries = import::parse(text, ImportFormat::Text, "Work", &[work]).unwrap();
+    let plan = import::plan(store, entries).await.unwrap();
+    let count = store.import(&plan).await.unwrap();
+
+    let buckets = store
+        .get_buckets_with_stats()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|stats| (stats.bucket.name, stats.cookie_count))
+        .collect();
+    let tagged = store
+        .query_cookies_with_buckets(&CookieQuery::new().tag(Some("diy")))
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.bucket_name)
+        .collect();
+    (buckets, count, tagged)
+}
+
+#[tokio::test]
+async fn imports_land_alike() {
+    let (buckets, count, tagged) = same_on_both!(imported);
+
+    assert_eq!(count, 2);
+    assert_eq!(buckets, vec![("Home".to_string(), 1), ("Work".to_string(), 2)]);
+    assert_eq!(tagged, vec!["Home".to_string()]);
+}
+
+async fn import_into_a_deleted_bucket(store: &impl CookieStore) -> (Result<usize, String>, usize) {
+    let work = store.create_bucket("Work").await.unwrap();
+    let entries = import::parse("Work: Shipped it\nCleared the inbox\n", ImportFormat::Text, "Work", std::slice::from_ref(&work)).unwrap();
+    let plan = import::plan(store, entries).await.unwrap();
+    store.delete_bucket(work.id, BucketCookies::Delete).await.unwrap();
+
+    let result = outcome(store.import(&plan).await);
+    (result, store.query_cookies(&CookieQuery::new()).await.unwrap().len())
+}
+
+#[tokio::test]
+async fn imports_into_a_bucket_deleted_since_planning_fail_alike() {
+    let (result, total) = same_on_both!(import_into_a_deleted_bucket);
+
+    assert_eq!(result, Err(format!("{:?}", CookieJarError::BucketNotFound(BucketRef::Name("Work".to_string())))));
+    assert_eq!(total, 0);
+}
+
+async fn schema(store: &impl CookieStore) -> (i64, usize) {
+    (store.schema_version().await.unwrap(), store.pending_migrations().await.unwrap().len())
+}
+
+#[tokio::test]
+async fn both_stores_are_at_the_latest_schema() {
+    let (version, pending) = same_on_both!(schema);
+
+    assert_eq!(version, db::migrations::latest_version());
+    assert_eq!(pending, 0);
+}